@@ -0,0 +1,221 @@
+//! software rasterizer: draws a display list into
+//! an RGBA canvas that can be saved as PPM or PNG
+
+use std::io;
+use std::io::Write;
+use crate::css::Color;
+use crate::font;
+use crate::layout::Rect;
+use crate::painting::{DisplayList, DisplayCommand, GlyphRun};
+use crate::png;
+
+pub struct Canvas {
+  pub pixels: Vec<Color>,
+  pub width: usize,
+  pub height: usize,
+  // active clip rects, innermost last
+  clips: Vec<Rect>,
+}
+
+// paint a display list into a new `width` x `height` canvas
+pub fn paint(list: &DisplayList, width: usize, height: usize) -> Canvas {
+  let mut canvas = Canvas::new(width, height);
+  for command in list.iter() {
+    canvas.paint_command(command);
+  }
+  canvas
+}
+
+impl Canvas {
+  // create a blank (white) canvas
+  pub fn new(width: usize, height: usize) -> Canvas {
+    let white = Color { r: 255, g: 255, b: 255, a: 255 };
+    Canvas {
+      pixels: vec![white; width * height],
+      width,
+      height,
+      clips: Vec::new(),
+    }
+  }
+
+  pub fn paint_command(&mut self, command: &DisplayCommand) {
+    match *command {
+      DisplayCommand::SolidColor(ref rect, ref color) => self.fill_rect(rect, color),
+      DisplayCommand::Border(ref rect, ref widths, ref color) => {
+        // top, bottom, left, right
+        self.fill_rect(&Rect { x: rect.x, y: rect.y, width: rect.width, height: widths.top }, color);
+        self.fill_rect(&Rect { x: rect.x, y: rect.y + rect.height - widths.bottom,
+                               width: rect.width, height: widths.bottom }, color);
+        self.fill_rect(&Rect { x: rect.x, y: rect.y + widths.top, width: widths.left,
+                               height: rect.height - widths.top - widths.bottom }, color);
+        self.fill_rect(&Rect { x: rect.x + rect.width - widths.right, y: rect.y + widths.top,
+                               width: widths.right,
+                               height: rect.height - widths.top - widths.bottom }, color);
+      }
+      DisplayCommand::Text(ref run, x, y, ref color) => self.draw_text(run, x, y, color),
+      DisplayCommand::PushClip(ref rect) => {
+        // nested clips intersect with the enclosing one
+        let clip = match self.clips.last() {
+          Some(outer) => intersect(outer, rect),
+          None => rect.clone(),
+        };
+        self.clips.push(clip);
+      }
+      DisplayCommand::PopClip => { self.clips.pop(); }
+    }
+  }
+
+  // fill `rect`, blending `color` over what's already there
+  fn fill_rect(&mut self, rect: &Rect, color: &Color) {
+    let (x0, y0, x1, y1) = self.pixel_bounds(rect);
+    for y in y0..y1 {
+      for x in x0..x1 {
+        self.blend_pixel(x, y, color);
+      }
+    }
+  }
+
+  // draw a run of glyphs from the bundled font, scaled to the run's
+  // font size, with the baseline at `baseline`
+  fn draw_text(&mut self, run: &GlyphRun, x: f32, baseline: f32, color: &Color) {
+    let font = font::Font::bundled();
+    let scale = run.font_size / font.em_size();
+    let top = baseline - font.metrics(run.font_size).ascent;
+
+    let mut pen_x = x;
+    for c in run.text.chars() {
+      let glyph = font.glyph(c);
+      let rect = Rect {
+        x: pen_x,
+        y: top,
+        width: glyph.width() as f32 * scale,
+        height: glyph.height() as f32 * scale,
+      };
+
+      // sample the glyph bitmap at each covered pixel's center
+      let (x0, y0, x1, y1) = self.pixel_bounds(&rect);
+      for py in y0..y1 {
+        for px in x0..x1 {
+          let gx = ((px as f32 + 0.5 - rect.x) / scale).floor();
+          let gy = ((py as f32 + 0.5 - rect.y) / scale).floor();
+          if gx >= 0.0 && gy >= 0.0 && glyph.is_set(gx as usize, gy as usize) {
+            self.blend_pixel(px, py, color);
+          }
+        }
+      }
+
+      pen_x += font.advance(c, run.font_size);
+    }
+  }
+
+  // pixel range (x0, y0, x1, y1) covered by `rect`, limited to
+  // the canvas and the current clip
+  fn pixel_bounds(&self, rect: &Rect) -> (usize, usize, usize, usize) {
+    let bounds = Rect { x: 0.0, y: 0.0, width: self.width as f32, height: self.height as f32 };
+    let clipped = match self.clips.last() {
+      Some(clip) => intersect(&intersect(&bounds, clip), rect),
+      None => intersect(&bounds, rect),
+    };
+
+    (clipped.x.round() as usize,
+     clipped.y.round() as usize,
+     (clipped.x + clipped.width).round() as usize,
+     (clipped.y + clipped.height).round() as usize)
+  }
+
+  // composite `color` over the pixel at (x, y)
+  fn blend_pixel(&mut self, x: usize, y: usize, color: &Color) {
+    let index = y * self.width + x;
+    let dst = self.pixels[index].clone();
+    self.pixels[index] = blend(color, &dst);
+  }
+
+  // write the canvas as a binary PPM (alpha is dropped)
+  pub fn write_ppm<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+
+    let mut data = Vec::with_capacity(self.pixels.len() * 3);
+    for pixel in self.pixels.iter() {
+      data.extend_from_slice(&[pixel.r, pixel.g, pixel.b]);
+    }
+    w.write_all(&data)
+  }
+
+  // write the canvas as an RGBA PNG
+  pub fn write_png<W: Write>(&self, w: &mut W) -> io::Result<()> {
+    let mut data = Vec::with_capacity(self.pixels.len() * 4);
+    for pixel in self.pixels.iter() {
+      data.extend_from_slice(&[pixel.r, pixel.g, pixel.b, pixel.a]);
+    }
+    png::encode(w, self.width, self.height, &data)
+  }
+}
+
+// "source over" compositing of `src` onto `dst`
+fn blend(src: &Color, dst: &Color) -> Color {
+  let src_a = src.a as f32 / 255.0;
+  let dst_a = dst.a as f32 / 255.0;
+  let out_a = src_a + dst_a * (1.0 - src_a);
+  if out_a == 0.0 {
+    return Color { r: 0, g: 0, b: 0, a: 0 };
+  }
+
+  let channel = |s: u8, d: u8| -> u8 {
+    let c = (s as f32 * src_a + d as f32 * dst_a * (1.0 - src_a)) / out_a;
+    c.round() as u8
+  };
+
+  Color {
+    r: channel(src.r, dst.r),
+    g: channel(src.g, dst.g),
+    b: channel(src.b, dst.b),
+    a: (out_a * 255.0).round() as u8,
+  }
+}
+
+// intersection of two rects (empty rects have zero width or height)
+fn intersect(a: &Rect, b: &Rect) -> Rect {
+  let x0 = a.x.max(b.x);
+  let y0 = a.y.max(b.y);
+  let x1 = (a.x + a.width).min(b.x + b.width);
+  let y1 = (a.y + a.height).min(b.y + b.height);
+
+  Rect { x: x0, y: y0, width: (x1 - x0).max(0.0), height: (y1 - y0).max(0.0) }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Canvas;
+  use crate::css::Color;
+  use crate::layout::Rect;
+  use crate::painting::DisplayCommand;
+
+  #[test]
+  fn fills_and_blends_rects() {
+    let mut canvas = Canvas::new(4, 4);
+    canvas.paint_command(&DisplayCommand::SolidColor(
+      Rect { x: 1.0, y: 1.0, width: 2.0, height: 2.0 }, Color { r: 0, g: 0, b: 0, a: 255 }));
+    canvas.paint_command(&DisplayCommand::SolidColor(
+      Rect { x: 0.0, y: 0.0, width: 4.0, height: 1.0 }, Color { r: 0, g: 0, b: 0, a: 128 }));
+
+    assert_eq!(canvas.pixels[0], Color { r: 127, g: 127, b: 127, a: 255 });
+    assert_eq!(canvas.pixels[4 + 1], Color { r: 0, g: 0, b: 0, a: 255 });
+    assert_eq!(canvas.pixels[4 * 3 + 3], Color { r: 255, g: 255, b: 255, a: 255 });
+  }
+
+  #[test]
+  fn clips_to_the_innermost_rect() {
+    let mut canvas = Canvas::new(4, 4);
+    let black = Color { r: 0, g: 0, b: 0, a: 255 };
+    canvas.paint_command(&DisplayCommand::PushClip(Rect { x: 0.0, y: 0.0, width: 2.0, height: 4.0 }));
+    canvas.paint_command(&DisplayCommand::PushClip(Rect { x: 1.0, y: 0.0, width: 3.0, height: 1.0 }));
+    canvas.paint_command(&DisplayCommand::SolidColor(
+      Rect { x: 0.0, y: 0.0, width: 4.0, height: 4.0 }, black.clone()));
+    canvas.paint_command(&DisplayCommand::PopClip);
+    canvas.paint_command(&DisplayCommand::PopClip);
+
+    let painted: Vec<bool> = canvas.pixels.iter().map(|p| *p == black).collect();
+    assert_eq!(&painted[0..4], &[false, true, false, false]);
+    assert!(painted[4..].iter().all(|p| !*p));
+  }
+}
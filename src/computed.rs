@@ -0,0 +1,365 @@
+//! computed values: a node's specified values with relative
+//! lengths, font keywords and colors resolved, which is what
+//! layout and painting read
+
+use crate::css::{Color, Unit, Value};
+use crate::layout::Display;
+use crate::properties;
+use crate::style::PropertyMap;
+
+// the font size of `medium`, and of the root before any styles
+pub const MEDIUM_FONT_SIZE: f32 = 16.0;
+
+// a computed length; percentages are left for layout to
+// resolve against the containing block
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+  Px(f32),
+  Percentage(f32),
+  Auto,
+}
+
+impl Length {
+  // resolve to px, with percentages relative to `reference`
+  // (auto resolves to 0)
+  pub fn to_px(&self, reference: f32) -> f32 {
+    match *self {
+      Length::Px(px) => px,
+      Length::Percentage(percentage) => percentage * reference / 100.0,
+      Length::Auto => 0.0,
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sides<T> {
+  pub top: T,
+  pub right: T,
+  pub bottom: T,
+  pub left: T,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FontStyle {
+  Normal,
+  Italic,
+  Oblique,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Overflow {
+  Visible,
+  Hidden,
+  Scroll,
+  Auto,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputedStyle {
+  pub display: Display,
+  pub width: Length,
+  pub height: Length,
+  pub margin: Sides<Length>,
+  pub padding: Sides<Length>,
+  pub border_width: Sides<f32>,
+  pub border_color: Color,
+  // transparent unless set
+  pub background_color: Color,
+  pub color: Color,
+  // in px
+  pub font_size: f32,
+  // 1 to 1000; normal is 400 and bold 700
+  pub font_weight: u16,
+  pub font_style: FontStyle,
+  pub font_family: String,
+  pub overflow: Overflow,
+}
+
+// the basic named colors
+const NAMED_COLORS: &[(&str, (u8, u8, u8))] = &[
+  ("aqua", (0, 255, 255)),
+  ("black", (0, 0, 0)),
+  ("blue", (0, 0, 255)),
+  ("fuchsia", (255, 0, 255)),
+  ("gray", (128, 128, 128)),
+  ("green", (0, 128, 0)),
+  ("grey", (128, 128, 128)),
+  ("lime", (0, 255, 0)),
+  ("maroon", (128, 0, 0)),
+  ("navy", (0, 0, 128)),
+  ("olive", (128, 128, 0)),
+  ("orange", (255, 165, 0)),
+  ("purple", (128, 0, 128)),
+  ("red", (255, 0, 0)),
+  ("silver", (192, 192, 192)),
+  ("teal", (0, 128, 128)),
+  ("white", (255, 255, 255)),
+  ("yellow", (255, 255, 0)),
+];
+
+// font sizes of the absolute size keywords, in px
+const FONT_SIZE_KEYWORDS: &[(&str, f32)] = &[
+  ("xx-small", 9.0),
+  ("x-small", 10.0),
+  ("small", 13.0),
+  ("medium", MEDIUM_FONT_SIZE),
+  ("large", 18.0),
+  ("x-large", 24.0),
+  ("xx-large", 32.0),
+  ("xxx-large", 48.0),
+];
+
+// how much `larger` and `smaller` scale the parent's font size
+const FONT_SIZE_RATIO: f32 = 1.2;
+
+const BLACK: Color = Color { r: 0, g: 0, b: 0, a: 255 };
+const TRANSPARENT: Color = Color { r: 0, g: 0, b: 0, a: 0 };
+
+impl ComputedStyle {
+  // compute the style of an element (or text node) from its
+  // specified values, given its parent's computed style and the
+  // root element's font size for `rem`
+  pub fn compute(values: &PropertyMap, parent: Option<&ComputedStyle>, root_font_size: f32) -> ComputedStyle {
+    let parent_font_size = parent.map_or(MEDIUM_FONT_SIZE, |parent| parent.font_size);
+    let parent_color = parent.map_or(BLACK, |parent| parent.color.clone());
+
+    // font-size comes first, as ems in other properties use it
+    let font_size = match values.get("font-size") {
+      Some(Value::Length(size, Unit::Px)) => *size,
+      Some(Value::Length(size, Unit::Em)) => size * parent_font_size,
+      Some(Value::Length(size, Unit::Rem)) => size * root_font_size,
+      Some(Value::Length(percentage, Unit::Percentage)) => percentage * parent_font_size / 100.0,
+      Some(Value::Keyword(keyword)) => match keyword.as_str() {
+        "larger" => parent_font_size * FONT_SIZE_RATIO,
+        "smaller" => parent_font_size / FONT_SIZE_RATIO,
+        keyword => FONT_SIZE_KEYWORDS.iter().find(|&&(name, _)| name == keyword)
+          .map_or(parent_font_size, |&(_, size)| size),
+      },
+      _ => parent_font_size,
+    }.max(0.0);
+
+    // anything that isn't a length (an unknown keyword, say) falls
+    // back to the property's initial value
+    let length = |name: &str| {
+      let resolve = |value: Option<&Value>| match value {
+        Some(Value::Length(n, Unit::Px)) => Some(Length::Px(*n)),
+        Some(Value::Length(n, Unit::Em)) => Some(Length::Px(n * font_size)),
+        Some(Value::Length(n, Unit::Rem)) => Some(Length::Px(n * root_font_size)),
+        Some(Value::Length(n, Unit::Percentage)) => Some(Length::Percentage(*n)),
+        // a unitless zero is the only plain number that's a length
+        Some(Value::Length(n, Unit::Number)) if *n == 0.0 => Some(Length::Px(0.0)),
+        Some(Value::Keyword(keyword)) if keyword == "auto" => Some(Length::Auto),
+        _ => None,
+      };
+      resolve(values.get(name))
+        .or_else(|| resolve(properties::initial_values().get(name)))
+        .unwrap_or(Length::Px(0.0))
+    };
+    let sides = |prefix: &str, suffix: &str| Sides {
+      top: length(&format!("{}-top{}", prefix, suffix)),
+      right: length(&format!("{}-right{}", prefix, suffix)),
+      bottom: length(&format!("{}-bottom{}", prefix, suffix)),
+      left: length(&format!("{}-left{}", prefix, suffix)),
+    };
+
+    let color = color(values.get("color"), &parent_color).unwrap_or(parent_color.clone());
+    let border_width = sides("border", "-width");
+    let keyword = |name: &str| match values.get(name) {
+      Some(Value::Keyword(keyword)) => keyword.clone(),
+      _ => String::new(),
+    };
+
+    ComputedStyle {
+      display: match keyword("display").as_str() {
+        // list items are blocks, without markers
+        "block" | "list-item" => Display::Block,
+        "none" => Display::None,
+        _ => Display::Inline,
+      },
+      width: match length("width") {
+        Length::Px(px) if px < 0.0 => Length::Auto,
+        width => width,
+      },
+      height: length("height"),
+      margin: sides("margin", ""),
+      padding: sides("padding", ""),
+      // percentages aren't allowed for border widths
+      border_width: Sides {
+        top: border_width.top.to_px(0.0),
+        right: border_width.right.to_px(0.0),
+        bottom: border_width.bottom.to_px(0.0),
+        left: border_width.left.to_px(0.0),
+      },
+      border_color: color_or(values.get("border-color"), &color, color.clone()),
+      background_color: color_or(values.get("background-color"), &color, TRANSPARENT),
+      font_size,
+      font_weight: font_weight(values.get("font-weight"), parent.map_or(400, |parent| parent.font_weight)),
+      font_style: match keyword("font-style").as_str() {
+        "italic" => FontStyle::Italic,
+        "oblique" => FontStyle::Oblique,
+        _ => FontStyle::Normal,
+      },
+      font_family: keyword("font-family"),
+      overflow: match keyword("overflow").as_str() {
+        "hidden" => Overflow::Hidden,
+        "scroll" => Overflow::Scroll,
+        "auto" => Overflow::Auto,
+        _ => Overflow::Visible,
+      },
+      color,
+    }
+  }
+
+  // the value a child inherits for `name` from a node with this
+  // style and the `specified` value: the specified value, unless
+  // it's relative to something the child would resolve it against
+  pub fn inherited_value(&self, name: &str, specified: &Value) -> Value {
+    let relative = match *specified {
+      Value::Length(_, Unit::Em | Unit::Rem | Unit::Percentage) => true,
+      Value::Keyword(ref keyword) => {
+        matches!(keyword.as_str(), "larger" | "smaller" | "bolder" | "lighter" | "currentcolor")
+      }
+      _ => false,
+    };
+
+    match name {
+      "font-size" if relative => Value::Length(self.font_size, Unit::Px),
+      "font-weight" if relative => Value::Length(self.font_weight as f32, Unit::Number),
+      "color" if relative => Value::ColorValue(self.color.clone()),
+      _ => specified.clone(),
+    }
+  }
+}
+
+// resolve a color value; `currentcolor` is `current`
+fn color(value: Option<&Value>, current: &Color) -> Option<Color> {
+  match value {
+    Some(Value::ColorValue(color)) => Some(color.clone()),
+    Some(Value::Keyword(keyword)) => match keyword.to_ascii_lowercase().as_str() {
+      "currentcolor" => Some(current.clone()),
+      "transparent" => Some(TRANSPARENT),
+      keyword => NAMED_COLORS.iter().find(|&&(name, _)| name == keyword)
+        .map(|&(_, (r, g, b))| Color { r, g, b, a: 255 }),
+    },
+    _ => None,
+  }
+}
+
+fn color_or(value: Option<&Value>, current: &Color, default: Color) -> Color {
+  color(value, current).unwrap_or(default)
+}
+
+// resolve a font weight, with `bolder` and `lighter` relative to
+// the parent's (as in CSS Fonts 4)
+fn font_weight(value: Option<&Value>, parent: u16) -> u16 {
+  match value {
+    Some(Value::Length(weight, Unit::Number)) if (1.0..=1000.0).contains(weight) => *weight as u16,
+    Some(Value::Keyword(keyword)) => match keyword.as_str() {
+      "normal" => 400,
+      "bold" => 700,
+      "bolder" => match parent {
+        0..=349 => 400,
+        350..=549 => 700,
+        _ => 900,
+      },
+      "lighter" => match parent {
+        0..=549 => 100,
+        550..=749 => 400,
+        _ => 700,
+      },
+      _ => parent,
+    },
+    _ => parent,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+  use super::{ComputedStyle, FontStyle, Length, Overflow};
+  use crate::css::{self, Color, Value};
+  use crate::layout::Display;
+  use crate::style::PropertyMap;
+
+  fn values(pairs: &[(&str, &str)]) -> PropertyMap {
+    let mut map = HashMap::new();
+    for &(name, value) in pairs.iter() {
+      map.insert(name.to_string(), css::parse_value(value).unwrap());
+    }
+    map
+  }
+
+  #[test]
+  fn resolves_font_relative_lengths() {
+    let parent = ComputedStyle::compute(&values(&[("font-size", "20px")]), None, 16.0);
+    let style = ComputedStyle::compute(&values(&[
+      ("font-size", "1.5em"), ("margin-top", "2em"), ("margin-left", "1rem"),
+      ("margin-right", "auto"), ("padding-left", "10%"), ("width", "3rem"),
+    ]), Some(&parent), 10.0);
+
+    assert_eq!(style.font_size, 30.0);
+    assert_eq!(style.margin.top, Length::Px(60.0));
+    assert_eq!(style.margin.left, Length::Px(10.0));
+    assert_eq!(style.margin.right, Length::Auto);
+    assert_eq!(style.padding.left, Length::Percentage(10.0));
+    assert_eq!(style.padding.left.to_px(200.0), 20.0);
+    assert_eq!(style.width, Length::Px(30.0));
+    assert_eq!(style.height, Length::Auto);
+
+    let percent = ComputedStyle::compute(&values(&[("font-size", "50%")]), Some(&parent), 16.0);
+    assert_eq!(percent.font_size, 10.0);
+  }
+
+  #[test]
+  fn non_lengths_fall_back_to_initial_values() {
+    let style = ComputedStyle::compute(&values(&[
+      ("margin-left", "-10px"), ("margin-top", "bogus"), ("width", "wide"), ("height", "5"),
+      ("padding-top", "0"),
+    ]), None, 16.0);
+    assert_eq!(style.margin.left, Length::Px(-10.0));
+    assert_eq!(style.margin.top, Length::Px(0.0));
+    assert_eq!(style.width, Length::Auto);
+    assert_eq!(style.height, Length::Auto);
+    assert_eq!(style.padding.top, Length::Px(0.0));
+  }
+
+  #[test]
+  fn resolves_keywords() {
+    let parent = ComputedStyle::compute(&values(&[("font-size", "large"), ("font-weight", "bold")]), None, 16.0);
+    assert_eq!((parent.font_size, parent.font_weight), (18.0, 700));
+
+    let style = ComputedStyle::compute(&values(&[
+      ("font-size", "larger"), ("font-weight", "bolder"), ("font-style", "italic"),
+      ("display", "list-item"), ("overflow", "hidden"),
+    ]), Some(&parent), 16.0);
+    assert_eq!(style.font_size, 18.0 * 1.2);
+    assert_eq!(style.font_weight, 900);
+    assert_eq!(style.font_style, FontStyle::Italic);
+    assert_eq!(style.display, Display::Block);
+    assert_eq!(style.overflow, Overflow::Hidden);
+
+    let lighter = ComputedStyle::compute(&values(&[("font-weight", "lighter")]), Some(&style), 16.0);
+    assert_eq!(lighter.font_weight, 700);
+    let bolder = Value::Keyword("bolder".to_string());
+    assert_eq!(style.inherited_value("font-weight", &bolder), Value::Length(900.0, css::Unit::Number));
+    assert_eq!(style.inherited_value("font-style", &bolder), bolder);
+  }
+
+  #[test]
+  fn resolves_colors() {
+    let parent = ComputedStyle::compute(&values(&[("color", "navy")]), None, 16.0);
+    assert_eq!(parent.color, Color { r: 0, g: 0, b: 128, a: 255 });
+
+    // unset colors are inherited, transparent or the current color
+    let style = ComputedStyle::compute(&values(&[]), Some(&parent), 16.0);
+    assert_eq!(style.color, parent.color);
+    assert_eq!(style.border_color, parent.color);
+    assert_eq!(style.background_color.a, 0);
+
+    let style = ComputedStyle::compute(&values(&[
+      ("color", "#ff0000"), ("border-color", "currentcolor"), ("background-color", "White"),
+    ]), Some(&parent), 16.0);
+    assert_eq!(style.border_color, Color { r: 255, g: 0, b: 0, a: 255 });
+    assert_eq!(style.background_color, Color { r: 255, g: 255, b: 255, a: 255 });
+  }
+}
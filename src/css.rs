@@ -0,0 +1,1354 @@
+//! CSS parser
+//! (only implements a very basic subset of CSS)
+
+use std::cmp::Reverse;
+use std::fmt;
+use crate::parser;
+use crate::parser::ParseError;
+use crate::properties;
+
+// id, class, tag
+pub type Specificity = (usize, usize, usize);
+
+#[derive(Debug, Clone)]
+pub struct Stylesheet {
+  pub rules: Vec<Rule>,
+}
+
+impl Stylesheet {
+  // pretty print a stylesheet
+  pub fn pretty_print(&self) {
+    fn indent(indent_level: usize) -> String {
+      let mut spaces = String::new();
+      let mut counter = 0;
+
+      loop {
+        if counter == indent_level { break }
+        counter += 1;
+        spaces.push_str("  ");
+      }
+
+      spaces
+    }
+
+    for rule in self.rules.iter() {
+
+      for selector in rule.selectors.iter() {
+        println!("{:?}", selector);
+      }
+      for declaration in rule.declarations.iter() {
+        println!("{}{}", indent(1), declaration.name);
+        println!("{}{}{}", indent(2), declaration.value,
+                 if declaration.important { " !important" } else { "" });
+      }
+
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+  pub selectors: Vec<Selector>,
+  pub declarations: Vec<Declaration>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Selector {
+  Simple(SimpleSelector),
+  // a selector for an element related to the simple selector's
+  // subject by the combinator, e.g. `ul >` in `ul > li`
+  Compound(Box<Selector>, Combinator, SimpleSelector),
+  // the element `:has()` is matching, at the start of each
+  // of its (relative) selectors
+  Scope,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Combinator {
+  // ` `
+  Descendant,
+  // `>`
+  Child,
+  // `+`
+  NextSibling,
+  // `~`
+  SubsequentSibling,
+}
+
+impl Selector {
+  // get back (id, class, tag) specificity of a Selector,
+  // summed over its simple selectors
+  pub fn specificity(&self) -> Specificity {
+    match *self {
+      Selector::Simple(ref simple) => simple.specificity(),
+      Selector::Compound(ref selector, _, ref simple) => {
+        add_specificity(selector.specificity(), simple.specificity())
+      }
+      Selector::Scope => (0, 0, 0),
+    }
+  }
+}
+
+impl SimpleSelector {
+  pub fn specificity(&self) -> Specificity {
+    let id = self.id.iter().len();
+    let class = self.class.len() + self.attributes.len();
+    let tag = self.tag_name.iter().len();
+    self.pseudo_classes.iter()
+      .fold((id, class, tag), |total, pseudo_class| add_specificity(total, pseudo_class.specificity()))
+  }
+}
+
+impl PseudoClass {
+  // `:where()` adds nothing, and `:is()`, `:not()` and `:has()`
+  // add their most specific argument instead of a class's worth
+  pub fn specificity(&self) -> Specificity {
+    match *self {
+      PseudoClass::Not(ref selectors) | PseudoClass::Is(ref selectors) |
+      PseudoClass::Has(ref selectors) => max_specificity(selectors),
+      PseudoClass::Where(_) => (0, 0, 0),
+      PseudoClass::NthChild(_, ref selectors) | PseudoClass::NthLastChild(_, ref selectors) => {
+        add_specificity((0, 1, 0), max_specificity(selectors))
+      }
+      _ => (0, 1, 0),
+    }
+  }
+}
+
+fn add_specificity(a: Specificity, b: Specificity) -> Specificity {
+  (a.0 + b.0, a.1 + b.1, a.2 + b.2)
+}
+
+fn max_specificity(selectors: &[Selector]) -> Specificity {
+  selectors.iter().map(|selector| selector.specificity()).max().unwrap_or((0, 0, 0))
+}
+
+// selectors are shown the way they'd be written in CSS
+impl fmt::Display for Selector {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      Selector::Simple(ref simple) => write!(f, "{}", simple),
+      Selector::Compound(ref selector, combinator, ref simple) => {
+        let combinator = match combinator {
+          Combinator::Descendant => " ",
+          Combinator::Child => " > ",
+          Combinator::NextSibling => " + ",
+          Combinator::SubsequentSibling => " ~ ",
+        };
+        write!(f, "{}{}{}", selector, combinator, simple)
+      }
+      Selector::Scope => Ok(()),
+    }
+  }
+}
+
+impl fmt::Display for SimpleSelector {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self.tag_name {
+      Some(ref tag_name) => write!(f, "{}", tag_name)?,
+      None if self.id.is_none() && self.class.is_empty() && self.attributes.is_empty()
+        && self.pseudo_classes.is_empty() => write!(f, "*")?,
+      None => {}
+    }
+    if let Some(ref id) = self.id {
+      write!(f, "#{}", id)?;
+    }
+    for class in self.class.iter() {
+      write!(f, ".{}", class)?;
+    }
+    for attribute in self.attributes.iter() {
+      write!(f, "{}", attribute)?;
+    }
+    for pseudo_class in self.pseudo_classes.iter() {
+      write!(f, "{}", pseudo_class)?;
+    }
+    Ok(())
+  }
+}
+
+impl fmt::Display for PseudoClass {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    // a comma-separated list, without the (empty) `:has()` scope
+    fn list(selectors: &[Selector]) -> String {
+      let selectors: Vec<String> = selectors.iter()
+        .map(|selector| selector.to_string().trim_start().to_string()).collect();
+      selectors.join(", ")
+    }
+    fn of(selectors: &[Selector]) -> String {
+      if selectors.is_empty() { String::new() } else { format!(" of {}", list(selectors)) }
+    }
+
+    match *self {
+      PseudoClass::Root => write!(f, ":root"),
+      PseudoClass::Empty => write!(f, ":empty"),
+      PseudoClass::FirstChild => write!(f, ":first-child"),
+      PseudoClass::LastChild => write!(f, ":last-child"),
+      PseudoClass::OnlyChild => write!(f, ":only-child"),
+      PseudoClass::NthChild(nth, ref selectors) => write!(f, ":nth-child({}{})", nth, of(selectors)),
+      PseudoClass::NthLastChild(nth, ref selectors) => write!(f, ":nth-last-child({}{})", nth, of(selectors)),
+      PseudoClass::NthOfType(nth) => write!(f, ":nth-of-type({})", nth),
+      PseudoClass::NthLastOfType(nth) => write!(f, ":nth-last-of-type({})", nth),
+      PseudoClass::Not(ref selectors) => write!(f, ":not({})", list(selectors)),
+      PseudoClass::Is(ref selectors) => write!(f, ":is({})", list(selectors)),
+      PseudoClass::Where(ref selectors) => write!(f, ":where({})", list(selectors)),
+      PseudoClass::Has(ref selectors) => write!(f, ":has({})", list(selectors)),
+    }
+  }
+}
+
+impl fmt::Display for Nth {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self.a {
+      0 => return write!(f, "{}", self.b),
+      1 => write!(f, "n")?,
+      -1 => write!(f, "-n")?,
+      a => write!(f, "{}n", a)?,
+    }
+    match self.b {
+      0 => Ok(()),
+      b if b > 0 => write!(f, "+{}", b),
+      b => write!(f, "{}", b),
+    }
+  }
+}
+
+impl fmt::Display for AttributeSelector {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let operator = match self.operator {
+      AttributeOperator::Exists => return write!(f, "[{}]", self.name),
+      AttributeOperator::Equals => "=",
+      AttributeOperator::Includes => "~=",
+      AttributeOperator::DashMatch => "|=",
+      AttributeOperator::Prefix => "^=",
+      AttributeOperator::Suffix => "$=",
+      AttributeOperator::Substring => "*=",
+    };
+    let value = self.value.replace('\\', "\\\\").replace('"', "\\\"");
+    write!(f, "[{}{}\"{}\"{}]", self.name, operator, value,
+           if self.case_insensitive { " i" } else { "" })
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct SimpleSelector {
+  pub tag_name: Option<String>,
+  pub id: Option<String>,
+  pub class: Vec<String>,
+  pub attributes: Vec<AttributeSelector>,
+  pub pseudo_classes: Vec<PseudoClass>,
+}
+
+// `[name]`, or `[name<operator>value]` with an optional
+// `i` or `s` flag
+#[derive(Debug, Clone)]
+pub struct AttributeSelector {
+  // lowercase, since HTML attribute names are
+  pub name: String,
+  pub operator: AttributeOperator,
+  pub value: String,
+  pub case_insensitive: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttributeOperator {
+  // `[name]`
+  Exists,
+  // `=`: exactly `value`
+  Equals,
+  // `~=`: `value` is one of the whitespace-separated words
+  Includes,
+  // `|=`: `value`, or `value` followed by `-`
+  DashMatch,
+  // `^=`
+  Prefix,
+  // `$=`
+  Suffix,
+  // `*=`
+  Substring,
+}
+
+#[derive(Debug, Clone)]
+pub enum PseudoClass {
+  Root,
+  Empty,
+  FirstChild,
+  LastChild,
+  OnlyChild,
+  // counting only the siblings that match the selectors
+  // after `of`, if there are any
+  NthChild(Nth, Vec<Selector>),
+  NthLastChild(Nth, Vec<Selector>),
+  NthOfType(Nth),
+  NthLastOfType(Nth),
+  Not(Vec<Selector>),
+  Is(Vec<Selector>),
+  Where(Vec<Selector>),
+  // each selector starts with `Selector::Scope`
+  Has(Vec<Selector>),
+}
+
+// the `an+b` argument of `:nth-child()` and friends
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Nth {
+  pub a: i32,
+  pub b: i32,
+}
+
+impl Nth {
+  // is `position` (counting from 1) `an+b` for some `n >= 0`?
+  pub fn matches(&self, position: usize) -> bool {
+    let offset = position as i64 - self.b as i64;
+    match self.a as i64 {
+      0 => offset == 0,
+      a => offset % a == 0 && offset / a >= 0,
+    }
+  }
+}
+
+impl AttributeSelector {
+  // does an attribute with this selector's name and the value
+  // `actual` match?
+  pub fn matches(&self, actual: &str) -> bool {
+    let (actual, value) = if self.case_insensitive {
+      (actual.to_ascii_lowercase(), self.value.to_ascii_lowercase())
+    } else {
+      (actual.to_string(), self.value.clone())
+    };
+    match self.operator {
+      AttributeOperator::Exists => true,
+      AttributeOperator::Equals => actual == value,
+      AttributeOperator::Includes => {
+        !value.is_empty() && !value.contains(|c: char| c.is_ascii_whitespace())
+          && actual.split_ascii_whitespace().any(|word| word == value)
+      }
+      AttributeOperator::DashMatch => {
+        actual == value || (actual.starts_with(&value) && actual[value.len()..].starts_with('-'))
+      }
+      AttributeOperator::Prefix => !value.is_empty() && actual.starts_with(&value),
+      AttributeOperator::Suffix => !value.is_empty() && actual.ends_with(&value),
+      AttributeOperator::Substring => !value.is_empty() && actual.contains(&value),
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct Declaration {
+  pub name: String,
+  pub value: Value,
+  // was it marked `!important`?
+  pub important: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+  Keyword(String),
+  Length(f32, Unit),
+  ColorValue(Color),
+  // the values of a shorthand that takes several, e.g. `margin: 0 auto`
+  List(Vec<Value>),
+}
+
+// values are shown the way they'd be written in CSS
+impl fmt::Display for Value {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      Value::Keyword(ref keyword) => write!(f, "{}", keyword),
+      Value::Length(length, ref unit) => write!(f, "{}{}", length, match *unit {
+        Unit::Px => "px",
+        Unit::Percentage => "%",
+        Unit::Em => "em",
+        Unit::Rem => "rem",
+        Unit::Number => "",
+      }),
+      Value::ColorValue(ref c) if c.a == 255 => write!(f, "#{:02x}{:02x}{:02x}", c.r, c.g, c.b),
+      Value::ColorValue(ref c) => write!(f, "#{:02x}{:02x}{:02x}{:02x}", c.r, c.g, c.b, c.a),
+      Value::List(ref values) => {
+        let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+        write!(f, "{}", values.join(" "))
+      }
+    }
+  }
+}
+
+impl Value {
+  // resolve a length to px; percentages are taken
+  // relative to `reference` (keywords resolve to 0)
+  pub fn to_px(&self, reference: f32) -> f32 {
+    match *self {
+      Value::Length(f, Unit::Px) => f,
+      Value::Length(f, Unit::Percentage) => f * reference / 100.0,
+      _ => 0.0
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Unit {
+  Px,
+  Percentage,
+  Em,
+  Rem,
+  // a plain number, e.g. `font-weight: 700`
+  Number,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Color {
+  pub r: u8,
+  pub g: u8,
+  pub b: u8,
+  pub a: u8,
+}
+
+struct Parser {
+  p : parser::Parser,
+  warnings: Vec<ParseError>,
+}
+
+impl Parser {
+
+  // parse rules until EOF, dropping (and warning about)
+  // any rule we can't parse
+  fn parse_rules(&mut self) -> Vec<Rule> {
+    let mut rules = Vec::new();
+    loop {
+      self.consume_whitespace_and_comments();
+      if self.p.eof() { break }
+
+      if self.p.starts_with("<!--") || self.p.starts_with("-->") {
+        // CDO and CDC are allowed (and ignored) between rules
+        self.p.consume_while(|c| matches!(c, '<' | '!' | '-' | '>'));
+      } else if self.p.next_char() == '@' {
+        self.skip_at_rule();
+      } else {
+        match self.parse_rule() {
+          Ok(rule) => rules.push(rule),
+          Err(error) => {
+            // drop the rest of the prelude and the rule's block
+            self.warnings.push(error);
+            self.skip_until(&['{']);
+            if !self.p.eof() {
+              self.skip_component_value();
+            }
+          }
+        }
+      }
+    }
+    rules
+  }
+
+  // skip an at-rule, which we don't support yet, up to the
+  // `;` or the end of its block
+  fn skip_at_rule(&mut self) {
+    let start = self.p.pos;
+    self.p.consume_char();
+    let name = self.parse_identifier();
+    self.warnings.push(self.p.error_at(start, "a style rule", &format!("`@{}`", name)));
+
+    self.skip_until(&['{', ';']);
+    if !self.p.eof() {
+      self.skip_component_value();
+    }
+  }
+
+  // parse string of valid identifier characters
+  fn parse_identifier(&mut self) -> String {
+    self.p.consume_while(valid_identifier_char)
+  }
+
+  // parse and return a Rule
+  fn parse_rule(&mut self) -> Result<Rule, ParseError> {
+    let selectors = self.parse_selectors()?;
+    Ok(Rule {
+      selectors,
+      declarations: self.parse_declarations(),
+    })
+  }
+
+  // consume and discard whitespace and comments
+  fn consume_whitespace_and_comments(&mut self) {
+    self.p.consume_whitespace();
+    while self.p.starts_with("/*") {
+      let start = self.p.pos;
+      match self.p.input[start + 2..].find("*/") {
+        Some(end) => self.p.pos = start + 2 + end + 2,
+        None => {
+          // an unterminated comment runs to the end of the input
+          self.warnings.push(self.p.error_at(start, "`*/` to close the comment", "end of input"));
+          self.p.pos = self.p.input.len();
+        }
+      }
+      self.p.consume_whitespace();
+    }
+  }
+
+  // skip component values (nested blocks, strings, comments and
+  // single chars) until one of `stop` at the top level, or EOF
+  fn skip_until(&mut self, stop: &[char]) {
+    loop {
+      self.consume_whitespace_and_comments();
+      if self.p.eof() || stop.contains(&self.p.next_char()) {
+        break;
+      }
+      self.skip_component_value();
+    }
+  }
+
+  // skip a single component value
+  fn skip_component_value(&mut self) {
+    let c = self.p.consume_char();
+    let close = match c {
+      '{' => '}',
+      '(' => ')',
+      '[' => ']',
+      '"' | '\'' => {
+        // skip a string, stopping at an unescaped newline
+        while !self.p.eof() {
+          match self.p.consume_char() {
+            '\\' if !self.p.eof() => { self.p.consume_char(); }
+            '\n' => break,
+            q if q == c => break,
+            _ => {}
+          }
+        }
+        return;
+      }
+      _ => return,
+    };
+
+    self.skip_until(&[close]);
+    if !self.p.eof() {
+      self.p.consume_char();
+    }
+  }
+
+  // parse list of simple selectors
+  fn parse_selectors(&mut self) -> Result<Vec<Selector>, ParseError> {
+    let mut selectors = Vec::new();
+
+    loop {
+      selectors.push(self.parse_selector()?);
+
+      if self.p.eof() {
+        return Err(self.p.error("`,` or `{`"));
+      }
+      match self.p.next_char() {
+        ',' => {
+          self.p.consume_char();
+          self.consume_whitespace_and_comments();
+        }
+        '{' => break,
+        _ => return Err(self.p.error("`,` or `{`")),
+      }
+    }
+
+    // sort by specificity (highest first)
+    selectors.sort_by_key(|selector| Reverse(selector.specificity()));
+
+    Ok(selectors)
+  }
+
+  // parse declarations in declaration block, dropping (and
+  // warning about) any declaration we can't parse
+  fn parse_declarations(&mut self) -> Vec<Declaration> {
+    self.p.consume_char(); // `{`, checked by parse_selectors
+
+    let mut declarations = Vec::new();
+    loop {
+      self.consume_whitespace_and_comments();
+
+      if self.p.eof() {
+        // the block is closed by the end of the input
+        self.warnings.push(self.p.error("`}`"));
+        break;
+      }
+      match self.p.next_char() {
+        // end of declaration block
+        '}' => {
+          self.p.consume_char();
+          break;
+        }
+        // empty declaration
+        ';' => {
+          self.p.consume_char();
+        }
+        _ => match self.parse_declaration() {
+          Ok(declaration) => declarations.push(declaration),
+          Err(error) => {
+            // drop everything up to the next `;` (or the end of the block)
+            self.warnings.push(error);
+            self.skip_until(&[';', '}']);
+            if self.p.starts_with(";") {
+              self.p.consume_char();
+            }
+          }
+        }
+      }
+    }
+
+    declarations
+  }
+
+  // parse and return a single Declaration
+  // (the `;` after the last declaration in a block is optional)
+  fn parse_declaration(&mut self) -> Result<Declaration, ParseError> {
+    let name = self.parse_identifier();
+    if name.is_empty() {
+      return Err(self.p.error("a property name"));
+    }
+
+    self.consume_whitespace_and_comments();
+    self.p.expect(":")?;
+    self.consume_whitespace_and_comments();
+
+    let mut value = self.parse_value()?;
+
+    // box shorthands take one to four values, one per side
+    if properties::is_box_shorthand(&name) {
+      let mut values = vec![value];
+      loop {
+        self.consume_whitespace_and_comments();
+        if values.len() == 4 || self.p.eof() || matches!(self.p.next_char(), ';' | '}' | '!') {
+          break;
+        }
+        values.push(self.parse_value()?);
+      }
+      value = if values.len() == 1 { values.pop().unwrap() } else { Value::List(values) };
+    }
+
+    self.consume_whitespace_and_comments();
+    let important = self.parse_important()?;
+    if !self.p.eof() && self.p.next_char() != '}' {
+      self.p.expect(";")?;
+    }
+
+    Ok(Declaration {
+      name,
+      value,
+      important,
+    })
+  }
+
+  // parse an optional `!important` after a value
+  fn parse_important(&mut self) -> Result<bool, ParseError> {
+    if self.p.eof() || self.p.next_char() != '!' {
+      return Ok(false);
+    }
+    self.p.consume_char();
+    self.consume_whitespace_and_comments();
+
+    let start = self.p.pos;
+    let keyword = self.parse_identifier();
+    if !keyword.eq_ignore_ascii_case("important") {
+      let found = if keyword.is_empty() { self.p.found() } else { format!("`{}`", keyword) };
+      return Err(self.p.error_at(start, "`important`", &found));
+    }
+    self.consume_whitespace_and_comments();
+    Ok(true)
+  }
+
+  // parse a declaration value
+  fn parse_value(&mut self) -> Result<Value, ParseError> {
+    if self.p.eof() {
+      return Err(self.p.error("a value"));
+    }
+
+    match self.p.next_char() {
+      '0'..='9' => self.parse_length(),
+      '-' | '+' | '.' if self.starts_with_number() => self.parse_length(),
+      '#' => self.parse_color(),
+      _ => {
+        let keyword = self.parse_identifier();
+        if keyword.is_empty() {
+          return Err(self.p.error("a value"));
+        }
+        Ok(Value::Keyword(keyword))
+      }
+    }
+  }
+
+  // parse a Value::Length, e.g., "123.4px"
+  fn parse_length(&mut self) -> Result<Value, ParseError> {
+    Ok(Value::Length(self.parse_float()?, self.parse_unit()?))
+  }
+
+  // does a number start here? (a digit, after an optional sign
+  // and an optional decimal point)
+  fn starts_with_number(&self) -> bool {
+    let mut rest = self.p.input[self.p.pos..].chars().peekable();
+    rest.next_if(|&c| c == '-' || c == '+');
+    rest.next_if(|&c| c == '.');
+    rest.next().is_some_and(|c| c.is_ascii_digit())
+  }
+
+  // parse 32-bit float, with an optional sign
+  fn parse_float(&mut self) -> Result<f32, ParseError> {
+    let start = self.p.pos;
+    let mut s = String::new();
+    if !self.p.eof() && matches!(self.p.next_char(), '-' | '+') {
+      s.push(self.p.consume_char());
+    }
+    s.push_str(&self.p.consume_while(|c| matches!(c, '0'..='9' | '.')));
+    s.parse().map_err(|_| self.p.error_at(start, "a number", &format!("`{}`", s)))
+  }
+
+  // parse the unit after a number (none makes it a plain number);
+  // a unit we don't support makes the declaration invalid
+  fn parse_unit(&mut self) -> Result<Unit, ParseError> {
+    let start = self.p.pos;
+    match self.parse_unit_value().as_str() {
+      "px" => Ok(Unit::Px),
+      "%" => Ok(Unit::Percentage),
+      "em" => Ok(Unit::Em),
+      "rem" => Ok(Unit::Rem),
+      "" => Ok(Unit::Number),
+      unit => Err(self.p.error_at(start, "a unit", &format!("`{}`", unit))),
+    }
+  }
+
+  // parse a valid unit value
+  fn parse_unit_value(&mut self) -> String {
+    self.p.consume_while(|c| valid_identifier_char(c) || c == '%').to_ascii_lowercase()
+  }
+
+  // parse hex color (only hex for now), e.g. #rrggbb
+  // or #rrggbbaa
+  fn parse_color(&mut self) -> Result<Value, ParseError> {
+    self.p.expect("#")?;
+    let r = self.parse_hex_pair()?;
+    let g = self.parse_hex_pair()?;
+    let b = self.parse_hex_pair()?;
+    let a = if !self.p.eof() && self.p.next_char().is_ascii_hexdigit() {
+      self.parse_hex_pair()?
+    } else {
+      255
+    };
+
+    Ok(Value::ColorValue(Color { r, g, b, a }))
+  }
+
+  // parse a hex pair
+  fn parse_hex_pair(&mut self) -> Result<u8, ParseError> {
+    let mut value = 0;
+    for _ in 0..2 {
+      match self.p.input[self.p.pos..].chars().next().and_then(|c| c.to_digit(16)) {
+        Some(digit) => {
+          self.p.consume_char();
+          value = value * 16 + digit as u8;
+        }
+        None => return Err(self.p.error("a hex digit")),
+      }
+    }
+
+    Ok(value)
+  }
+
+  // parse a single selector
+  // parse simple selectors joined by combinators, up to
+  // (and not including) a `,` or `{`
+  fn parse_selector(&mut self) -> Result<Selector, ParseError> {
+    let selector = Selector::Simple(self.parse_nonempty_simple_selector()?);
+    self.parse_combinators(selector)
+  }
+
+  // parse any combinators and simple selectors after `selector`
+  fn parse_combinators(&mut self, mut selector: Selector) -> Result<Selector, ParseError> {
+    loop {
+      let start = self.p.pos;
+      self.consume_whitespace_and_comments();
+      if self.p.eof() {
+        return Ok(selector);
+      }
+
+      let combinator = match self.p.next_char() {
+        ',' | '{' | ')' => return Ok(selector),
+        '>' => Combinator::Child,
+        '+' => Combinator::NextSibling,
+        '~' => Combinator::SubsequentSibling,
+        _ if self.p.pos > start => Combinator::Descendant,
+        _ => return Err(self.p.error("a combinator, `,` or `{`")),
+      };
+      if combinator != Combinator::Descendant {
+        self.p.consume_char();
+        self.consume_whitespace_and_comments();
+      }
+
+      let simple = self.parse_nonempty_simple_selector()?;
+      selector = Selector::Compound(Box::new(selector), combinator, simple);
+    }
+  }
+
+  fn parse_nonempty_simple_selector(&mut self) -> Result<SimpleSelector, ParseError> {
+    let start = self.p.pos;
+    let selector = self.parse_simple_selector()?;
+    if self.p.pos == start {
+      return Err(self.p.error("a selector"));
+    }
+    Ok(selector)
+  }
+
+  fn parse_simple_selector(&mut self) -> Result<SimpleSelector, ParseError> {
+    let mut selector = SimpleSelector {
+      tag_name: None,
+      id: None,
+      class: Vec::new(),
+      attributes: Vec::new(),
+      pseudo_classes: Vec::new(),
+    };
+
+    while !self.p.eof() {
+      match self.p.next_char() {
+        '#' => {
+          self.p.consume_char();
+          selector.id = Some(self.parse_identifier());
+        }
+        '.' => {
+          self.p.consume_char();
+          selector.class.push(self.parse_identifier());
+        }
+        '[' => {
+          self.p.consume_char();
+          selector.attributes.push(self.parse_attribute_selector()?);
+        }
+        ':' => {
+          self.p.consume_char();
+          selector.pseudo_classes.push(self.parse_pseudo_class()?);
+        }
+        '*' => {
+          self.p.consume_char();
+        }
+        c if valid_identifier_char(c) => {
+          selector.tag_name = Some(self.parse_identifier())
+        }
+        _ => break
+      }
+    }
+
+    Ok(selector)
+  }
+
+  // parse the inside of `[...]`, and the closing `]`
+  fn parse_attribute_selector(&mut self) -> Result<AttributeSelector, ParseError> {
+    self.consume_whitespace_and_comments();
+    let name = self.parse_identifier().to_ascii_lowercase();
+    if name.is_empty() {
+      return Err(self.p.error("an attribute name"));
+    }
+    self.consume_whitespace_and_comments();
+
+    let mut selector = AttributeSelector {
+      name,
+      operator: AttributeOperator::Exists,
+      value: String::new(),
+      case_insensitive: false,
+    };
+    if self.p.starts_with("]") {
+      self.p.consume_char();
+      return Ok(selector);
+    }
+
+    let operators = [
+      ("=", AttributeOperator::Equals),
+      ("~=", AttributeOperator::Includes),
+      ("|=", AttributeOperator::DashMatch),
+      ("^=", AttributeOperator::Prefix),
+      ("$=", AttributeOperator::Suffix),
+      ("*=", AttributeOperator::Substring),
+    ];
+    selector.operator = match operators.iter().find(|&&(text, _)| self.p.starts_with(text)) {
+      Some(&(text, operator)) => {
+        self.p.pos += text.len();
+        operator
+      }
+      None => return Err(self.p.error("`]` or an attribute operator")),
+    };
+    self.consume_whitespace_and_comments();
+
+    selector.value = match self.p.eof() {
+      false if matches!(self.p.next_char(), '"' | '\'') => self.parse_string()?,
+      _ => {
+        let value = self.parse_identifier();
+        if value.is_empty() {
+          return Err(self.p.error("an attribute value"));
+        }
+        value
+      }
+    };
+    self.consume_whitespace_and_comments();
+
+    let start = self.p.pos;
+    match self.parse_identifier().to_ascii_lowercase().as_str() {
+      "" => {}
+      "i" => selector.case_insensitive = true,
+      "s" => {}
+      flag => return Err(self.p.error_at(start, "`]`, `i` or `s`", &format!("`{}`", flag))),
+    }
+    self.consume_whitespace_and_comments();
+    self.p.expect("]")?;
+
+    Ok(selector)
+  }
+
+  // parse a pseudo-class after its `:` (pseudo-elements
+  // and unknown pseudo-classes are errors)
+  fn parse_pseudo_class(&mut self) -> Result<PseudoClass, ParseError> {
+    let start = self.p.pos;
+    let name = self.parse_identifier().to_ascii_lowercase();
+    let functional = self.p.starts_with("(");
+
+    let pseudo_class = match (name.as_str(), functional) {
+      ("root", false) => PseudoClass::Root,
+      ("empty", false) => PseudoClass::Empty,
+      ("first-child", false) => PseudoClass::FirstChild,
+      ("last-child", false) => PseudoClass::LastChild,
+      ("only-child", false) => PseudoClass::OnlyChild,
+      ("nth-child" | "nth-last-child" | "nth-of-type" | "nth-last-of-type" |
+       "not" | "is" | "where" | "has", true) => {
+        self.p.consume_char();
+        self.consume_whitespace_and_comments();
+        let pseudo_class = match name.as_str() {
+          "nth-child" => PseudoClass::NthChild(self.parse_nth()?, self.parse_of_selectors()?),
+          "nth-last-child" => PseudoClass::NthLastChild(self.parse_nth()?, self.parse_of_selectors()?),
+          "nth-of-type" => PseudoClass::NthOfType(self.parse_nth()?),
+          "nth-last-of-type" => PseudoClass::NthLastOfType(self.parse_nth()?),
+          "not" => PseudoClass::Not(self.parse_selector_list(false)?),
+          "is" => PseudoClass::Is(self.parse_selector_list(false)?),
+          "where" => PseudoClass::Where(self.parse_selector_list(false)?),
+          _ => PseudoClass::Has(self.parse_selector_list(true)?),
+        };
+        self.consume_whitespace_and_comments();
+        self.p.expect(")")?;
+        pseudo_class
+      }
+      _ => {
+        let found = if functional { format!("`:{}(`", name) } else { format!("`:{}`", name) };
+        return Err(self.p.error_at(start - 1, "a supported pseudo-class", &found));
+      }
+    };
+    Ok(pseudo_class)
+  }
+
+  // parse comma-separated selectors up to a `)`; relative ones
+  // (for `:has()`) may start with a combinator, and are
+  // anchored at `Selector::Scope`
+  fn parse_selector_list(&mut self, relative: bool) -> Result<Vec<Selector>, ParseError> {
+    let mut selectors = Vec::new();
+    loop {
+      self.consume_whitespace_and_comments();
+      let selector = if relative {
+        let combinator = match self.p.eof() {
+          true => Combinator::Descendant,
+          false => match self.p.next_char() {
+            '>' => Combinator::Child,
+            '+' => Combinator::NextSibling,
+            '~' => Combinator::SubsequentSibling,
+            _ => Combinator::Descendant,
+          }
+        };
+        if combinator != Combinator::Descendant {
+          self.p.consume_char();
+          self.consume_whitespace_and_comments();
+        }
+        let simple = self.parse_nonempty_simple_selector()?;
+        self.parse_combinators(Selector::Compound(Box::new(Selector::Scope), combinator, simple))?
+      } else {
+        self.parse_selector()?
+      };
+      selectors.push(selector);
+
+      if self.p.starts_with(",") {
+        self.p.consume_char();
+      } else {
+        return Ok(selectors);
+      }
+    }
+  }
+
+  // parse the selectors after `of` in `:nth-child()`, if any
+  fn parse_of_selectors(&mut self) -> Result<Vec<Selector>, ParseError> {
+    self.consume_whitespace_and_comments();
+    let start = self.p.pos;
+    if self.parse_identifier().eq_ignore_ascii_case("of") {
+      self.parse_selector_list(false)
+    } else {
+      self.p.pos = start;
+      Ok(Vec::new())
+    }
+  }
+
+  // parse `an+b`, `odd` or `even`
+  fn parse_nth(&mut self) -> Result<Nth, ParseError> {
+    for &(keyword, nth) in [("odd", Nth { a: 2, b: 1 }), ("even", Nth { a: 2, b: 0 })].iter() {
+      let start = self.p.pos;
+      if self.parse_identifier().eq_ignore_ascii_case(keyword) {
+        return Ok(nth);
+      }
+      self.p.pos = start;
+    }
+
+    let sign = self.parse_sign();
+    let digits = self.p.consume_while(|c| c.is_ascii_digit());
+    if self.p.eof() || !matches!(self.p.next_char(), 'n' | 'N') {
+      if digits.is_empty() {
+        return Err(self.p.error("`an+b`, `odd` or `even`"));
+      }
+      return Ok(Nth { a: 0, b: sign * self.parse_integer(&digits)? });
+    }
+
+    self.p.consume_char();
+    let a = sign * if digits.is_empty() { 1 } else { self.parse_integer(&digits)? };
+    self.consume_whitespace_and_comments();
+    if self.p.eof() || !matches!(self.p.next_char(), '+' | '-') {
+      return Ok(Nth { a, b: 0 });
+    }
+    let sign = self.parse_sign();
+    self.consume_whitespace_and_comments();
+    let digits = self.p.consume_while(|c| c.is_ascii_digit());
+    if digits.is_empty() {
+      return Err(self.p.error("an integer"));
+    }
+    Ok(Nth { a, b: sign * self.parse_integer(&digits)? })
+  }
+
+  // consume an optional `+` or `-`
+  fn parse_sign(&mut self) -> i32 {
+    if self.p.starts_with("-") {
+      self.p.consume_char();
+      -1
+    } else {
+      if self.p.starts_with("+") {
+        self.p.consume_char();
+      }
+      1
+    }
+  }
+
+  fn parse_integer(&self, digits: &str) -> Result<i32, ParseError> {
+    digits.parse().map_err(|_| {
+      self.p.error_at(self.p.pos - digits.len(), "a smaller integer", &format!("`{}`", digits))
+    })
+  }
+
+  // parse a quoted string, unescaping `\` escapes
+  fn parse_string(&mut self) -> Result<String, ParseError> {
+    let quote = self.p.consume_char();
+    let mut value = String::new();
+    loop {
+      if self.p.eof() || self.p.next_char() == '\n' {
+        return Err(self.p.error(&format!("`{}` to close the string", quote)));
+      }
+      match self.p.consume_char() {
+        '\\' if !self.p.eof() => value.push(self.p.consume_char()),
+        c if c == quote => return Ok(value),
+        c => value.push(c),
+      }
+    }
+  }
+}
+
+// parse a source string and return a Stylesheet, skipping
+// anything that can't be parsed
+pub fn parse(source: String) -> Stylesheet {
+  parse_with_warnings(source).0
+}
+
+// parse a source string, returning the Stylesheet along with
+// a warning for each part that was skipped
+pub fn parse_with_warnings(source: String) -> (Stylesheet, Vec<ParseError>) {
+  let mut parser = Parser {
+    p: parser::Parser::new(source),
+    warnings: Vec::new(),
+  };
+  let rules = parser.parse_rules();
+  (Stylesheet { rules }, parser.warnings)
+}
+
+// parse a single value, like the one in a declaration
+pub fn parse_value(source: &str) -> Result<Value, ParseError> {
+  let mut parser = Parser {
+    p: parser::Parser::new(source.to_string()),
+    warnings: Vec::new(),
+  };
+  let value = parser.parse_value()?;
+  if !parser.p.eof() {
+    return Err(parser.p.error("the end of the value"));
+  }
+  Ok(value)
+}
+
+// is `c` a valid identifier char?
+// (needs better unicode support)
+fn valid_identifier_char(c: char) -> bool {
+  matches!(c, 'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_')
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{parse, parse_value, parse_with_warnings, Nth, PseudoClass, Selector, Value, Unit, Color};
+
+  #[test]
+  fn parses_rules_and_declarations() {
+    let sheet = parse("h1, .foo { color: #ff0000; width: 23.5%; display: block; }".to_string());
+    assert_eq!(sheet.rules.len(), 1);
+
+    let rule = &sheet.rules[0];
+    assert_eq!(rule.selectors.len(), 2);
+    assert_eq!(rule.declarations.len(), 3);
+    assert_eq!(rule.declarations[0].name.as_str(), "color");
+    assert_eq!(rule.declarations[0].value, Value::ColorValue(Color { r: 255, g: 0, b: 0, a: 255 }));
+    assert_eq!(rule.declarations[1].value, Value::Length(23.5, Unit::Percentage));
+    assert_eq!(rule.declarations[2].value, Value::Keyword("block".to_string()));
+  }
+
+  #[test]
+  fn selectors_are_sorted_by_specificity() {
+    let sheet = parse("div, #id, .a.b { margin: 0px; }".to_string());
+    let specificities: Vec<(usize, usize, usize)> =
+      sheet.rules[0].selectors.iter().map(|s| s.specificity()).collect();
+    assert_eq!(specificities, vec![(1, 0, 0), (0, 2, 0), (0, 0, 1)]);
+
+    match sheet.rules[0].selectors[0] {
+      Selector::Simple(ref simple) => assert_eq!(simple.id, Some("id".to_string())),
+      _ => panic!("expected a simple selector"),
+    }
+  }
+
+  #[test]
+  fn parses_combinators() {
+    let sheet = parse("ul>li a, .nav  *.x/**/+ #y ~p, div\n> p { margin: 0px; }".to_string());
+    let selectors: Vec<String> = sheet.rules[0].selectors.iter().map(|s| s.to_string()).collect();
+    assert_eq!(selectors, vec![".nav .x + #y ~ p", "ul > li a", "div > p"]);
+    assert_eq!(sheet.rules[0].selectors[0].specificity(), (1, 2, 1));
+
+    let (sheet, warnings) = parse_with_warnings("a > { color: #000000; } a > > b, p {} i {}".to_string());
+    assert_eq!(sheet.rules.len(), 1);
+    let found: Vec<&str> = warnings.iter().map(|w| w.found.as_str()).collect();
+    assert_eq!(found, vec!["`{`", "`>`"]);
+  }
+
+  #[test]
+  fn parses_attribute_selectors() {
+    let sheet = parse("input[type=text], a[ HREF ^= 'http:' ], [lang|=\"en\" I][data-x] {}".to_string());
+    let selectors: Vec<String> = sheet.rules[0].selectors.iter().map(|s| s.to_string()).collect();
+    assert_eq!(selectors, vec!["[lang|=\"en\" i][data-x]", "input[type=\"text\"]", "a[href^=\"http:\"]"]);
+    assert_eq!(sheet.rules[0].selectors[0].specificity(), (0, 2, 0));
+
+    let (sheet, warnings) = parse_with_warnings("[] {} [a=] {} [a=b x] {} [a!b] {} [a] {} [a=\"b] {}".to_string());
+    assert_eq!(sheet.rules.len(), 1);
+    let expected: Vec<&str> = warnings.iter().map(|w| w.expected.as_str()).collect();
+    assert_eq!(expected, vec!["an attribute name", "an attribute value", "`]`, `i` or `s`",
+                              "`]` or an attribute operator", "`\"` to close the string"]);
+  }
+
+  #[test]
+  fn matches_attribute_values() {
+    let selector = |source: &str| {
+      let sheet = parse(format!("{} {{}}", source));
+      match sheet.rules[0].selectors[0] {
+        Selector::Simple(ref simple) => simple.attributes[0].clone(),
+        _ => panic!("expected a simple selector"),
+      }
+    };
+
+    assert!(selector("[a]").matches(""));
+    assert!(selector("[a=b]").matches("b") && !selector("[a=b]").matches("B"));
+    assert!(selector("[a=b i]").matches("B"));
+    assert!(selector("[a~=b]").matches("x b y") && !selector("[a~=b]").matches("bb"));
+    assert!(!selector("[a~=\"\"]").matches(""));
+    assert!(selector("[a|=en]").matches("en-US") && selector("[a|=en]").matches("en"));
+    assert!(!selector("[a|=en]").matches("english"));
+    assert!(selector("[a^=ht]").matches("http") && !selector("[a^=\"\"]").matches("http"));
+    assert!(selector("[a$=\".png\"]").matches("x.png") && selector("[a*=og]").matches("logo"));
+  }
+
+  #[test]
+  fn parses_pseudo_classes() {
+    let sheet = parse("li:NTH-CHILD( -n + 3 ), :nth-child(odd of .a, p), a:not(.b, #c), :where(#d) p,
+                       :has(> img, + p) {}".to_string());
+    let selectors: Vec<String> = sheet.rules[0].selectors.iter().map(|s| s.to_string()).collect();
+    assert_eq!(selectors, vec!["a:not(.b, #c)", ":nth-child(2n+1 of .a, p)", "li:nth-child(-n+3)",
+                               ":where(#d) p", ":has(> img, + p)"]);
+    let specificities: Vec<(usize, usize, usize)> = sheet.rules[0].selectors.iter()
+      .map(|s| s.specificity()).collect();
+    assert_eq!(specificities, vec![(1, 0, 1), (0, 2, 0), (0, 1, 1), (0, 0, 1), (0, 0, 1)]);
+
+    let nth = |source: &str| match parse(format!(":nth-of-type({}) {{}}", source)).rules[0].selectors[0] {
+      Selector::Simple(ref simple) => match simple.pseudo_classes[0] {
+        PseudoClass::NthOfType(nth) => (nth.a, nth.b),
+        _ => panic!("expected :nth-of-type"),
+      },
+      _ => panic!("expected a simple selector"),
+    };
+    assert_eq!(nth("even"), (2, 0));
+    assert_eq!(nth("5"), (0, 5));
+    assert_eq!(nth("-2n-1"), (-2, -1));
+    assert_eq!(nth("n"), (1, 0));
+
+    let (sheet, warnings) = parse_with_warnings("a:hover {} ::before {} :nth-child(x) {} :not(a {} p:empty {}".to_string());
+    assert_eq!(sheet.rules.len(), 1);
+    let found: Vec<&str> = warnings.iter().map(|w| w.found.as_str()).collect();
+    assert_eq!(found, vec!["`:hover`", "`:`", "`x`", "`{`"]);
+  }
+
+  #[test]
+  fn overflowing_nth_drops_the_rule() {
+    let (sheet, warnings) = parse_with_warnings("li:nth-child(99999999999) { width: 1px; } p {}".to_string());
+    assert_eq!(sheet.rules.len(), 1);
+    assert_eq!(sheet.rules[0].selectors[0].to_string(), "p");
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!((warnings[0].column, warnings[0].expected.as_str(), warnings[0].found.as_str()),
+               (14, "a smaller integer", "`99999999999`"));
+  }
+
+  #[test]
+  fn nth_matches_positions() {
+    let positions = |a, b| (1..=7).filter(|&position| Nth { a, b }.matches(position)).collect::<Vec<usize>>();
+    assert_eq!(positions(2, 1), vec![1, 3, 5, 7]);
+    assert_eq!(positions(-1, 3), vec![1, 2, 3]);
+    assert_eq!(positions(3, -1), vec![2, 5]);
+    assert_eq!(positions(0, 4), vec![4]);
+  }
+
+  #[test]
+  fn skips_comments() {
+    let sheet = parse("/* one */ /* two */ p { /* three */ height: 10px; }".to_string());
+    assert_eq!(sheet.rules.len(), 1);
+    assert_eq!(sheet.rules[0].declarations[0].value, Value::Length(10.0, Unit::Px));
+  }
+
+  #[test]
+  fn colors_with_alpha() {
+    let sheet = parse("p { color: #11223380; }".to_string());
+    assert_eq!(sheet.rules[0].declarations[0].value,
+               Value::ColorValue(Color { r: 0x11, g: 0x22, b: 0x33, a: 0x80 }));
+  }
+
+  #[test]
+  fn recovers_from_invalid_declarations() {
+    let (sheet, warnings) = parse_with_warnings("
+      p { *zoom: 1; color: #ff00zz; margin: (a; b) 0px; width: 1px; height: 2px }
+      div { width: 3px; }".to_string());
+    assert_eq!(sheet.rules.len(), 2);
+
+    let names: Vec<&str> = sheet.rules[0].declarations.iter().map(|d| d.name.as_str()).collect();
+    assert_eq!(names, vec!["width", "height"]);
+    assert_eq!(sheet.rules[1].declarations[0].value, Value::Length(3.0, Unit::Px));
+
+    assert_eq!(warnings.len(), 3);
+    assert_eq!((warnings[0].line, warnings[0].column), (2, 11));
+    assert_eq!(warnings[0].expected.as_str(), "a property name");
+    assert_eq!((warnings[1].line, warnings[1].column), (2, 33));
+    assert_eq!(warnings[1].expected.as_str(), "a hex digit");
+    assert_eq!(warnings[2].expected.as_str(), "a value");
+  }
+
+  #[test]
+  fn drops_invalid_rules_and_at_rules() {
+    let (sheet, warnings) = parse_with_warnings("
+      @import \"a.css\";
+      a ! b { color: #000000; }
+      @media print { p { color: #ff0000; } }
+      p, { width: 1px; }
+      <!-- .ok { height: 2px; } -->".to_string());
+    assert_eq!(sheet.rules.len(), 1);
+    assert_eq!(sheet.rules[0].declarations[0].name.as_str(), "height");
+
+    let found: Vec<&str> = warnings.iter().map(|w| w.found.as_str()).collect();
+    assert_eq!(found, vec!["`@import`", "`!`", "`@media`", "`{`"]);
+  }
+
+  #[test]
+  fn unterminated_input_closes_everything() {
+    let (sheet, warnings) = parse_with_warnings("p { width: 1px; /* open".to_string());
+    assert_eq!(sheet.rules[0].declarations.len(), 1);
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(warnings[0].expected.as_str(), "`*/` to close the comment");
+    assert_eq!(warnings[1].expected.as_str(), "`}`");
+  }
+
+  #[test]
+  fn units_stop_at_the_end_of_the_value() {
+    let (sheet, warnings) = parse_with_warnings("a{width:10px}".to_string());
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    assert_eq!(sheet.rules[0].declarations[0].value, Value::Length(10.0, Unit::Px));
+
+    let sheet = parse("a{width:1px!important}".to_string());
+    assert_eq!(sheet.rules[0].declarations[0].value, Value::Length(1.0, Unit::Px));
+    assert!(sheet.rules[0].declarations[0].important);
+
+    let (sheet, warnings) = parse_with_warnings("a{margin:1em}b{width:50%}".to_string());
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    assert_eq!(sheet.rules.len(), 2);
+    assert_eq!(sheet.rules[0].declarations[0].value, Value::Length(1.0, Unit::Em));
+    assert_eq!(sheet.rules[1].declarations[0].value, Value::Length(50.0, Unit::Percentage));
+  }
+
+  #[test]
+  fn unknown_units_make_declarations_invalid() {
+    let (sheet, warnings) = parse_with_warnings("a { width: 10vw; height: 2PX; font-weight: 700; width: 5px }".to_string());
+    let declarations: Vec<String> = sheet.rules[0].declarations.iter()
+      .map(|d| format!("{}: {}", d.name, d.value)).collect();
+    assert_eq!(declarations, vec!["height: 2px", "font-weight: 700", "width: 5px"]);
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!((warnings[0].column, warnings[0].expected.as_str(), warnings[0].found.as_str()),
+               (14, "a unit", "`vw`"));
+    assert!(parse_value("1pt").is_err());
+  }
+
+  #[test]
+  fn box_shorthands_take_up_to_four_values() {
+    let (sheet, warnings) = parse_with_warnings(
+      "a { margin: 0 auto; padding: 1px 2px 3px !important; border-width: 1px; width: 1px 2px; margin: 1px 2px 3px 4px 5px }".to_string());
+    let declarations: Vec<String> = sheet.rules[0].declarations.iter()
+      .map(|d| format!("{}: {}", d.name, d.value)).collect();
+    assert_eq!(declarations, vec!["margin: 0 auto", "padding: 1px 2px 3px", "border-width: 1px"]);
+    assert_eq!(sheet.rules[0].declarations[0].value,
+               Value::List(vec![Value::Length(0.0, Unit::Number), Value::Keyword("auto".to_string())]));
+    assert!(sheet.rules[0].declarations[1].important);
+
+    // other properties still take one value, and shorthands no more than four
+    let found: Vec<&str> = warnings.iter().map(|w| w.found.as_str()).collect();
+    assert_eq!(found, vec!["`2`", "`5`"]);
+  }
+
+  #[test]
+  fn parses_signed_numbers() {
+    assert_eq!(parse_value("-10px"), Ok(Value::Length(-10.0, Unit::Px)));
+    assert_eq!(parse_value("+1.5em"), Ok(Value::Length(1.5, Unit::Em)));
+    assert_eq!(parse_value(".5em"), Ok(Value::Length(0.5, Unit::Em)));
+    assert_eq!(parse_value("-.5"), Ok(Value::Length(-0.5, Unit::Number)));
+
+    // a sign without a number is still the start of a keyword
+    assert_eq!(parse_value("-webkit-box"), Ok(Value::Keyword("-webkit-box".to_string())));
+  }
+
+  #[test]
+  fn parses_important_declarations() {
+    let (sheet, warnings) = parse_with_warnings("p { color: #ff0000 ! IMPORTANT; width: 1px !/**/important } a { width: 2px !imp; height: 1px }".to_string());
+    let important: Vec<bool> = sheet.rules[0].declarations.iter().map(|d| d.important).collect();
+    assert_eq!(important, vec![true, true]);
+
+    // a bad `!` drops the whole declaration
+    assert_eq!(sheet.rules[1].declarations.len(), 1);
+    assert!(!sheet.rules[1].declarations[0].important);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!((warnings[0].column, warnings[0].found.as_str()), (77, "`imp`"));
+  }
+
+  #[test]
+  fn parses_single_values() {
+    assert_eq!(parse_value("1.5em"), Ok(Value::Length(1.5, Unit::Em)));
+    assert_eq!(parse_value("2REM"), Ok(Value::Length(2.0, Unit::Rem)));
+    assert_eq!(parse_value("transparent"), Ok(Value::Keyword("transparent".to_string())));
+    assert_eq!(parse_value("1px 2px").unwrap_err().expected.as_str(), "the end of the value");
+  }
+
+  #[test]
+  fn lengths_resolve_to_px() {
+    assert_eq!(Value::Length(12.0, Unit::Px).to_px(200.0), 12.0);
+    assert_eq!(Value::Length(25.0, Unit::Percentage).to_px(200.0), 50.0);
+    assert_eq!(Value::Keyword("auto".to_string()).to_px(200.0), 0.0);
+  }
+}
@@ -0,0 +1,486 @@
+//! DOM (only implements text, comment, doctype and element nodes)
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use crate::css;
+
+pub type AttrMap = HashMap<String, String>;
+
+#[derive(Debug)]
+pub struct Node {
+  pub children: Vec<Node>,
+  pub node_type: NodeType,
+}
+
+#[derive(Debug)]
+pub enum NodeType {
+  Text(String),
+  Comment(String),
+  Doctype(DoctypeData),
+  Element(ElementData),
+  Document(DocumentData),
+}
+
+#[derive(Debug, Clone)]
+pub struct ElementData {
+  pub tag_name: String,
+  pub attributes: AttrMap,
+}
+
+// missing identifiers are empty strings, as in the DOM
+#[derive(Debug, Clone, PartialEq)]
+pub struct DoctypeData {
+  pub name: String,
+  pub public_id: String,
+  pub system_id: String,
+}
+
+#[derive(Debug)]
+pub struct DocumentData {
+  // derived from the tree by `Node::update_stylesheets`
+  stylesheets: Vec<DocumentStylesheet>,
+  mode: DocumentMode,
+}
+
+// a stylesheet from a <style> or <link rel=stylesheet> element
+#[derive(Debug, Clone)]
+pub struct DocumentStylesheet {
+  pub source: StylesheetSource,
+  // the element's `media` attribute, if it has one
+  pub media: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum StylesheetSource {
+  // the parsed contents of a <style> element
+  Inline(css::Stylesheet),
+  // the `href` of a <link>, for the embedder to load
+  Link(String),
+}
+
+// how closely the document asked to follow the standards,
+// decided by its doctype
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentMode {
+  NoQuirks,
+  LimitedQuirks,
+  Quirks,
+}
+
+// constructors
+pub fn text (data: String) -> Node {
+  Node {
+    children: vec![],
+    node_type: NodeType::Text(data),
+  }
+}
+
+pub fn comment (data: String) -> Node {
+  Node {
+    children: vec![],
+    node_type: NodeType::Comment(data),
+  }
+}
+
+pub fn doctype (name: String, public_id: String, system_id: String) -> Node {
+  Node {
+    children: vec![],
+    node_type: NodeType::Doctype(DoctypeData { name, public_id, system_id }),
+  }
+}
+
+pub fn elem (tag_name: String, attrs: AttrMap, children: Vec<Node>) -> Node {
+  Node {
+    children,
+    node_type: NodeType::Element(ElementData {
+      attributes: attrs,
+      tag_name,
+    })
+  }
+}
+
+// the document's stylesheets are gathered from `children`
+pub fn document(children: Vec<Node>) -> Node {
+  let mut document = Node {
+    children,
+    node_type: NodeType::Document(DocumentData::new(DocumentMode::NoQuirks))
+  };
+  document.update_stylesheets();
+  document
+}
+
+impl Node {
+  // re-derive a document's stylesheets from its <style> and
+  // <link rel=stylesheet> elements, e.g. after editing the tree
+  pub fn update_stylesheets(&mut self) {
+    self.set_stylesheets(&mut |style| css::parse(style.text_content()));
+  }
+
+  // like `update_stylesheets`, given the already parsed contents
+  // of each <style> element, in tree order
+  pub(crate) fn update_stylesheets_from(&mut self, parsed: Vec<css::Stylesheet>) {
+    let mut parsed = parsed.into_iter();
+    self.set_stylesheets(&mut |style| parsed.next().unwrap_or_else(|| css::parse(style.text_content())));
+  }
+
+  fn set_stylesheets(&mut self, parse_style: &mut dyn FnMut(&Node) -> css::Stylesheet) {
+    let mut stylesheets = Vec::new();
+    self.collect_stylesheets(&mut stylesheets, parse_style);
+    if let NodeType::Document(ref mut document) = self.node_type {
+      document.stylesheets = stylesheets;
+    }
+  }
+
+  // stylesheets from `&self` down, in tree order
+  fn collect_stylesheets(&self, stylesheets: &mut Vec<DocumentStylesheet>,
+                         parse_style: &mut dyn FnMut(&Node) -> css::Stylesheet) {
+    if let NodeType::Element(ref elem) = self.node_type {
+      let source = match elem.tag_name.as_str() {
+        "style" => Some(StylesheetSource::Inline(parse_style(self))),
+        "link" if elem.rel().contains("stylesheet") => {
+          elem.get_attribute("href").map(|href| StylesheetSource::Link(href.clone()))
+        }
+        _ => None,
+      };
+      if let Some(source) = source {
+        stylesheets.push(DocumentStylesheet { source, media: elem.get_attribute("media").cloned() });
+      }
+    }
+
+    for child in self.children.iter() {
+      child.collect_stylesheets(stylesheets, parse_style);
+    }
+  }
+
+  // the text of all text nodes from `&self` down, in tree order
+  pub fn text_content(&self) -> String {
+    match self.node_type {
+      NodeType::Text(ref text) => text.clone(),
+      _ => self.children.iter().map(|child| child.text_content()).collect(),
+    }
+  }
+
+  // pretty print the DOM tree from `&self` down,
+  // starting from indent level `indent_level`
+  pub fn pretty_print(&self, indent_level: usize) {
+    let mut out = String::new();
+    self.dump_into(&mut out, indent_level);
+    print!("{}", out);
+  }
+
+  // dump the tree as text, one node per line with children indented
+  pub fn dump(&self) -> String {
+    let mut out = String::new();
+    self.dump_into(&mut out, 0);
+    out
+  }
+
+  fn dump_into(&self, out: &mut String, indent_level: usize) {
+    let mut spaces = String::new();
+    let mut counter = 0;
+
+    loop {
+      if counter == indent_level { break }
+      counter += 1;
+      spaces.push_str("  ");
+    }
+
+    out.push_str(&format!("{}{}\n", spaces, self.node_type));
+    for child_node in self.children.iter() {
+      child_node.dump_into(out, indent_level + 1)
+    }
+  }
+}
+
+// a one-line description of the node, e.g. `<p class="intro">`
+// (attributes are sorted so the output is stable)
+impl fmt::Display for NodeType {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      NodeType::Text(ref text) => write!(f, "\"{}\"", text.escape_default()),
+      NodeType::Comment(ref comment) => write!(f, "<!--{}-->", comment),
+      NodeType::Doctype(ref doctype) => {
+        write!(f, "<!DOCTYPE {}", doctype.name)?;
+        if !doctype.public_id.is_empty() || !doctype.system_id.is_empty() {
+          write!(f, " \"{}\" \"{}\"", doctype.public_id, doctype.system_id)?;
+        }
+        write!(f, ">")
+      }
+      NodeType::Element(ref elem) => {
+        let mut names: Vec<&String> = elem.attributes.keys().collect();
+        names.sort();
+
+        write!(f, "<{}", elem.tag_name)?;
+        for name in names.iter() {
+          write!(f, " {}=\"{}\"", name, elem.attributes[*name])?;
+        }
+        write!(f, ">")
+      }
+      NodeType::Document(_) => write!(f, "#document"),
+    }
+  }
+}
+
+impl ElementData {
+
+  // get attribute from self.attributes
+  pub fn get_attribute(&self, key: &str) -> Option<&String> {
+    self.attributes.get(key)
+  }
+
+  // get id
+  pub fn id(&self) -> Option<&String> {
+    self.get_attribute("id")
+  }
+
+  // get classes as HashSet<&str>
+  pub fn classes(&self) -> HashSet<&str> {
+    match self.get_attribute("class") {
+      Some(classlist) => classlist.split(' ').collect(),
+      None => HashSet::new()
+    }
+  }
+
+  // get the link types in `rel`, lowercased
+  pub fn rel(&self) -> HashSet<String> {
+    match self.get_attribute("rel") {
+      Some(rel) => rel.split_ascii_whitespace().map(|kind| kind.to_ascii_lowercase()).collect(),
+      None => HashSet::new()
+    }
+  }
+
+}
+
+impl DocumentData {
+  // a document with no stylesheets yet
+  pub fn new(mode: DocumentMode) -> DocumentData {
+    DocumentData { stylesheets: Vec::new(), mode }
+  }
+
+  // stylesheets in tree order
+  pub fn stylesheets(&self) -> &[DocumentStylesheet] {
+    &self.stylesheets
+  }
+
+  pub fn mode(&self) -> DocumentMode {
+    self.mode
+  }
+
+  pub(crate) fn set_mode(&mut self, mode: DocumentMode) {
+    self.mode = mode;
+  }
+
+  // quirks mode proper, not limited quirks
+  pub fn in_quirks_mode(&self) -> bool {
+    self.mode == DocumentMode::Quirks
+  }
+}
+
+impl DocumentStylesheet {
+  // does the sheet apply on screen? `media` is only checked for
+  // the media types in it, not for media features
+  pub fn applies_to_screen(&self) -> bool {
+    match self.media {
+      Some(ref media) if !media.trim().is_empty() => media.split(',').any(|query| {
+        let media_type = query.split_ascii_whitespace().next().unwrap_or("").to_ascii_lowercase();
+        media_type == "all" || media_type == "screen"
+      }),
+      _ => true,
+    }
+  }
+
+  // the parsed stylesheet, if it's inline
+  pub fn inline(&self) -> Option<&css::Stylesheet> {
+    match self.source {
+      StylesheetSource::Inline(ref sheet) => Some(sheet),
+      StylesheetSource::Link(_) => None,
+    }
+  }
+}
+
+// a node along with the path to it from the root, so that
+// selectors can look at its ancestors and siblings
+#[derive(Debug, Clone, Copy)]
+pub struct NodeRef<'a> {
+  pub node: &'a Node,
+  pub parent: Option<&'a NodeRef<'a>>,
+  // the node's index among its parent's children
+  pub index: usize,
+}
+
+impl<'a> NodeRef<'a> {
+  pub fn root(node: &'a Node) -> NodeRef<'a> {
+    NodeRef { node, parent: None, index: 0 }
+  }
+
+  pub fn element(&self) -> Option<&'a ElementData> {
+    match self.node.node_type {
+      NodeType::Element(ref elem) => Some(elem),
+      _ => None,
+    }
+  }
+
+  // the parent, if it's an element (the root element's
+  // parent is the document)
+  pub fn parent_element(&self) -> Option<&'a NodeRef<'a>> {
+    self.parent.filter(|parent| parent.element().is_some())
+  }
+
+  // element ancestors, nearest first
+  pub fn ancestor_elements(&self) -> impl Iterator<Item = &'a NodeRef<'a>> {
+    std::iter::successors(self.parent_element(), |parent| parent.parent_element())
+  }
+
+  // element siblings before this node, nearest first
+  pub fn previous_element_siblings(&self) -> impl Iterator<Item = NodeRef<'a>> {
+    let parent = self.parent;
+    let siblings = match parent {
+      Some(parent) => &parent.node.children[..self.index],
+      None => &[],
+    };
+    siblings.iter().enumerate().rev()
+      .map(move |(index, node)| NodeRef { node, parent, index })
+      .filter(|sibling| sibling.element().is_some())
+  }
+
+  // element siblings after this node, nearest first
+  pub fn next_element_siblings(&self) -> impl Iterator<Item = NodeRef<'a>> {
+    let parent = self.parent;
+    let siblings = match parent {
+      Some(parent) => &parent.node.children[self.index + 1..],
+      None => &[],
+    };
+    let start = self.index + 1;
+    siblings.iter().enumerate()
+      .map(move |(index, node)| NodeRef { node, parent, index: start + index })
+      .filter(|sibling| sibling.element().is_some())
+  }
+
+  // does `test` hold for any element below this node?
+  // (elements are visited in tree order)
+  pub fn any_descendant_element(&self, test: &mut dyn FnMut(&NodeRef) -> bool) -> bool {
+    self.node.children.iter().enumerate().any(|(index, node)| {
+      let child = NodeRef { node, parent: Some(self), index };
+      (child.element().is_some() && test(&child)) || child.any_descendant_element(test)
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::HashMap;
+  use super::{elem, text, comment, doctype, document, NodeRef, NodeType, StylesheetSource};
+
+  fn attrs(pairs: &[(&str, &str)]) -> super::AttrMap {
+    let mut map = HashMap::new();
+    for &(name, value) in pairs.iter() {
+      map.insert(name.to_string(), value.to_string());
+    }
+    map
+  }
+
+  #[test]
+  fn element_attributes() {
+    let node = elem("div".to_string(), attrs(&[("id", "main"), ("class", "a b")]), vec![]);
+    match node.node_type {
+      NodeType::Element(ref data) => {
+        assert_eq!(data.id(), Some(&"main".to_string()));
+        let classes = data.classes();
+        assert_eq!(classes.len(), 2);
+        assert!(classes.contains(&"a") && classes.contains(&"b"));
+        assert_eq!(data.get_attribute("title"), None);
+      }
+      _ => panic!("expected an element"),
+    }
+  }
+
+  #[test]
+  fn dump_sorts_attributes_and_indents_children() {
+    let p = elem("p".to_string(), attrs(&[("id", "x"), ("class", "y")]),
+                 vec![text("hi\n".to_string()), comment(" note ".to_string())]);
+    let root = document(vec![p]);
+
+    assert_eq!(root.dump(),
+               "#document\n  <p class=\"y\" id=\"x\">\n    \"hi\\n\"\n    <!-- note -->\n");
+  }
+
+  #[test]
+  fn doctypes_show_their_identifiers() {
+    let html5 = doctype("html".to_string(), String::new(), String::new());
+    assert_eq!(html5.node_type.to_string(), "<!DOCTYPE html>");
+
+    let legacy = doctype("html".to_string(), "-//W3C//DTD HTML 4.01//EN".to_string(), String::new());
+    assert_eq!(legacy.node_type.to_string(), "<!DOCTYPE html \"-//W3C//DTD HTML 4.01//EN\" \"\">");
+  }
+
+  #[test]
+  fn stylesheets_are_gathered_in_tree_order() {
+    let style = |css: &str, media: &[(&str, &str)]| {
+      elem("style".to_string(), attrs(media), vec![text(css.to_string())])
+    };
+    let link = elem("link".to_string(), attrs(&[("rel", "StyleSheet"), ("href", "a.css")]), vec![]);
+    let icon = elem("link".to_string(), attrs(&[("rel", "icon"), ("href", "a.png")]), vec![]);
+    let head = elem("head".to_string(), attrs(&[]), vec![style("p {}", &[]), link, icon]);
+    let body = elem("body".to_string(), attrs(&[]), vec![style("a {} b {}", &[("media", "print")])]);
+    let mut root = document(vec![head, body]);
+
+    let stylesheets = |root: &super::Node| match root.node_type {
+      NodeType::Document(ref document) => document.stylesheets().to_vec(),
+      _ => panic!("expected a document"),
+    };
+    let sheets = stylesheets(&root);
+    assert_eq!(sheets.len(), 3);
+    assert_eq!(sheets[0].inline().unwrap().rules.len(), 1);
+    match sheets[1].source {
+      StylesheetSource::Link(ref href) => assert_eq!(href.as_str(), "a.css"),
+      _ => panic!("expected a link"),
+    }
+    assert_eq!(sheets[2].inline().unwrap().rules.len(), 2);
+    assert!(sheets[0].applies_to_screen());
+    assert!(!sheets[2].applies_to_screen());
+
+    // edits show up once the sheets are updated
+    root.children[1].children.clear();
+    root.update_stylesheets();
+    assert_eq!(stylesheets(&root).len(), 2);
+  }
+
+  #[test]
+  fn node_refs_walk_ancestors_and_siblings() {
+    let ul = elem("ul".to_string(), attrs(&[]), vec![
+      elem("li".to_string(), attrs(&[("id", "a")]), vec![]),
+      text("x".to_string()),
+      elem("li".to_string(), attrs(&[("id", "b")]), vec![]),
+      elem("li".to_string(), attrs(&[("id", "c")]), vec![text("y".to_string())]),
+    ]);
+    let root = document(vec![ul]);
+
+    let document = NodeRef::root(&root);
+    let ul = NodeRef { node: &root.children[0], parent: Some(&document), index: 0 };
+    let li = NodeRef { node: &ul.node.children[3], parent: Some(&ul), index: 3 };
+    let text = NodeRef { node: &li.node.children[0], parent: Some(&li), index: 0 };
+
+    let ids: Vec<&str> = li.previous_element_siblings()
+      .map(|sibling| sibling.element().unwrap().id().unwrap().as_str()).collect();
+    assert_eq!(ids, vec!["b", "a"]);
+    assert_eq!(ul.previous_element_siblings().count(), 0);
+
+    let tags: Vec<&str> = text.ancestor_elements()
+      .map(|ancestor| ancestor.element().unwrap().tag_name.as_str()).collect();
+    assert_eq!(tags, vec!["li", "ul"]);
+    let first = NodeRef { node: &ul.node.children[0], parent: Some(&ul), index: 0 };
+    let ids: Vec<&str> = first.next_element_siblings()
+      .map(|sibling| sibling.element().unwrap().id().unwrap().as_str()).collect();
+    assert_eq!(ids, vec!["b", "c"]);
+
+    let mut visited = Vec::new();
+    assert!(!document.any_descendant_element(&mut |elem| {
+      visited.push(elem.element().unwrap().tag_name.clone());
+      false
+    }));
+    assert_eq!(visited, vec!["ul", "li", "li", "li"]);
+    assert!(ul.parent_element().is_none());
+    assert!(document.element().is_none());
+  }
+}
@@ -0,0 +1,290 @@
+//! bundled bitmap font
+//! (a single monospace face, rasterized from DejaVu Sans Mono
+//! at 16px; other sizes are scaled from it)
+
+// size of the bitmaps, in px
+const EM_SIZE: f32 = 16.0;
+const CELL_WIDTH: usize = 10;
+const CELL_HEIGHT: usize = 19;
+
+// rows above and below the baseline
+const ASCENT: usize = 15;
+const DESCENT: usize = 4;
+
+// first and last chars in the table
+const FIRST_CHAR: char = ' ';
+const LAST_CHAR: char = '~';
+
+pub struct Font {
+  glyphs: &'static [[u16; 19]],
+}
+
+// vertical metrics at a given font size, in px
+#[derive(Debug, Clone)]
+pub struct FontMetrics {
+  pub ascent: f32,
+  pub descent: f32,
+  pub line_height: f32,
+}
+
+// a single glyph bitmap, CELL_WIDTH by CELL_HEIGHT, with the
+// baseline ASCENT rows down from the top
+pub struct Glyph {
+  rows: &'static [u16; 19],
+}
+
+impl Glyph {
+  pub fn width(&self) -> usize {
+    CELL_WIDTH
+  }
+
+  pub fn height(&self) -> usize {
+    CELL_HEIGHT
+  }
+
+  // is the pixel at (x, y) set?
+  pub fn is_set(&self, x: usize, y: usize) -> bool {
+    x < CELL_WIDTH && y < CELL_HEIGHT && self.rows[y] & (0x8000 >> x) != 0
+  }
+}
+
+impl Font {
+  // the font bundled with the engine
+  pub fn bundled() -> Font {
+    Font { glyphs: &GLYPHS }
+  }
+
+  // the size of the bitmaps, in px
+  pub fn em_size(&self) -> f32 {
+    EM_SIZE
+  }
+
+  // ascent, descent and line height at `font_size`
+  pub fn metrics(&self, font_size: f32) -> FontMetrics {
+    let scale = font_size / EM_SIZE;
+    FontMetrics {
+      ascent: ASCENT as f32 * scale,
+      descent: DESCENT as f32 * scale,
+      line_height: (ASCENT + DESCENT) as f32 * scale,
+    }
+  }
+
+  // horizontal advance of `c` at `font_size`
+  // (the font is monospace, so every char has the same advance)
+  pub fn advance(&self, _c: char, font_size: f32) -> f32 {
+    CELL_WIDTH as f32 * font_size / EM_SIZE
+  }
+
+  // width of `text` at `font_size`
+  pub fn measure(&self, text: &str, font_size: f32) -> f32 {
+    text.chars().fold(0.0, |width, c| width + self.advance(c, font_size))
+  }
+
+  // glyph bitmap for `c`; chars outside the table get
+  // the replacement glyph
+  pub fn glyph(&self, c: char) -> Glyph {
+    let index = if c >= FIRST_CHAR && c <= LAST_CHAR {
+      c as usize - FIRST_CHAR as usize
+    } else {
+      self.glyphs.len() - 1
+    };
+
+    Glyph { rows: &self.glyphs[index] }
+  }
+}
+
+// the glyphs are derived from DejaVu Sans Mono, which is
+// distributed under this license (DejaVu's own changes to
+// Bitstream Vera are in the public domain):
+//
+// Fonts are (c) Bitstream (see below). DejaVu changes are in public domain.
+//
+// Bitstream Vera Fonts Copyright
+// ------------------------------
+//
+// Copyright (c) 2003 by Bitstream, Inc. All Rights Reserved. Bitstream Vera is
+// a trademark of Bitstream, Inc.
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of the fonts accompanying this license ("Fonts") and associated
+// documentation files (the "Font Software"), to reproduce and distribute the
+// Font Software, including without limitation the rights to use, copy, merge,
+// publish, distribute, and/or sell copies of the Font Software, and to permit
+// persons to whom the Font Software is furnished to do so, subject to the
+// following conditions:
+//
+// The above copyright and trademark notices and this permission notice shall
+// be included in all copies of one or more of the Font Software typefaces.
+//
+// The Font Software may be modified, altered, or added to, and in particular
+// the designs of glyphs or characters in the Fonts may be modified and
+// additional glyphs or characters may be added to the Fonts, only if the fonts
+// are renamed to names not containing either the words "Bitstream" or the word
+// "Vera".
+//
+// This License becomes null and void to the extent applicable to Fonts or Font
+// Software that has been modified and is distributed under the "Bitstream
+// Vera" names.
+//
+// The Font Software may be sold as part of a larger software package but no
+// copy of one or more of the Font Software typefaces may be sold by itself.
+//
+// THE FONT SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS
+// OR IMPLIED, INCLUDING BUT NOT LIMITED TO ANY WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT OF COPYRIGHT, PATENT,
+// TRADEMARK, OR OTHER RIGHT. IN NO EVENT SHALL BITSTREAM OR THE GNOME
+// FOUNDATION BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER LIABILITY, INCLUDING
+// ANY GENERAL, SPECIAL, INDIRECT, INCIDENTAL, OR CONSEQUENTIAL DAMAGES,
+// WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM, OUT OF
+// THE USE OR INABILITY TO USE THE FONT SOFTWARE OR FROM OTHER DEALINGS IN THE
+// FONT SOFTWARE.
+//
+// Except as contained in this notice, the names of Gnome, the Gnome
+// Foundation, and Bitstream Inc., shall not be used in advertising or
+// otherwise to promote the sale, use or other dealings in this Font Software
+// without prior written authorization from the Gnome Foundation or Bitstream
+// Inc., respectively. For further information, contact: fonts at gnome dot
+// org.
+
+// one row per line of the cell, leftmost pixel in the high bit;
+// printable ASCII followed by the replacement glyph
+static GLYPHS: [[u16; 19]; 96] = [
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000], // ' '
+  [0x0000, 0x0000, 0x0000, 0x0800, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0000, 0x0000, 0x0c00, 0x0c00, 0x0000, 0x0000, 0x0000, 0x0000], // '!'
+  [0x0000, 0x0000, 0x0000, 0x1200, 0x1200, 0x1200, 0x1200, 0x1200, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000], // '"'
+  [0x0000, 0x0000, 0x0000, 0x0800, 0x0900, 0x0900, 0x1b00, 0x7fc0, 0x1200, 0x1200, 0xff00, 0xff80, 0x2400, 0x2400, 0x6c00, 0x0000, 0x0000, 0x0000, 0x0000], // '#'
+  [0x0000, 0x0000, 0x0000, 0x0c00, 0x0c00, 0x3f00, 0x2c00, 0x6c00, 0x2c00, 0x3e00, 0x0f00, 0x0d80, 0x0d80, 0x6d00, 0x3e00, 0x0c00, 0x0c00, 0x0000, 0x0000], // '$'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x7000, 0xc800, 0x8800, 0x5800, 0x7300, 0x1c00, 0x6700, 0x0480, 0x0c80, 0x0480, 0x0700, 0x0000, 0x0000, 0x0000, 0x0000], // '%'
+  [0x0000, 0x0000, 0x0000, 0x1e00, 0x3200, 0x2000, 0x2000, 0x3000, 0x3800, 0x4880, 0x4480, 0xc680, 0x4380, 0x6300, 0x3f80, 0x0000, 0x0000, 0x0000, 0x0000], // '&'
+  [0x0000, 0x0000, 0x0000, 0x0800, 0x0c00, 0x0c00, 0x0c00, 0x0800, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000], // '\''
+  [0x0000, 0x0000, 0x0000, 0x0600, 0x0400, 0x0c00, 0x0800, 0x0800, 0x1800, 0x1800, 0x1800, 0x1800, 0x0800, 0x0800, 0x0c00, 0x0400, 0x0600, 0x0000, 0x0000], // '('
+  [0x0000, 0x0000, 0x0000, 0x1000, 0x0800, 0x0800, 0x0c00, 0x0c00, 0x0400, 0x0400, 0x0400, 0x0400, 0x0c00, 0x0c00, 0x0800, 0x1800, 0x1000, 0x0000, 0x0000], // ')'
+  [0x0000, 0x0000, 0x0000, 0x0800, 0x0800, 0x3b00, 0x1c00, 0x1e00, 0x6900, 0x0800, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000], // '*'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0c00, 0x0c00, 0x0c00, 0x7f80, 0x7f80, 0x0c00, 0x0c00, 0x0c00, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000], // '+'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0c00, 0x0c00, 0x0800, 0x1800, 0x0000, 0x0000], // ','
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x1e00, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000], // '-'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0800, 0x0c00, 0x0c00, 0x0000, 0x0000, 0x0000, 0x0000], // '.'
+  [0x0000, 0x0000, 0x0000, 0x0100, 0x0300, 0x0200, 0x0600, 0x0600, 0x0c00, 0x0c00, 0x0800, 0x1800, 0x1000, 0x3000, 0x2000, 0x6000, 0x4000, 0x0000, 0x0000], // '/'
+  [0x0000, 0x0000, 0x0000, 0x1e00, 0x3f00, 0x2300, 0x6100, 0x6180, 0x6d80, 0x6d80, 0x6180, 0x6100, 0x6100, 0x3300, 0x1e00, 0x0000, 0x0000, 0x0000, 0x0000], // '0'
+  [0x0000, 0x0000, 0x0000, 0x0c00, 0x3c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x3f80, 0x0000, 0x0000, 0x0000, 0x0000], // '1'
+  [0x0000, 0x0000, 0x0000, 0x3c00, 0x7f00, 0x0300, 0x0300, 0x0300, 0x0300, 0x0600, 0x0c00, 0x1800, 0x3000, 0x6000, 0x7f00, 0x0000, 0x0000, 0x0000, 0x0000], // '2'
+  [0x0000, 0x0000, 0x0000, 0x3c00, 0x7f00, 0x0300, 0x0300, 0x0300, 0x1e00, 0x1e00, 0x0100, 0x0100, 0x0100, 0x4300, 0x7e00, 0x0000, 0x0000, 0x0000, 0x0000], // '3'
+  [0x0000, 0x0000, 0x0000, 0x0600, 0x0600, 0x0e00, 0x1a00, 0x1200, 0x3200, 0x2200, 0x4200, 0x7f80, 0x0200, 0x0200, 0x0200, 0x0000, 0x0000, 0x0000, 0x0000], // '4'
+  [0x0000, 0x0000, 0x0000, 0x3e00, 0x7e00, 0x6000, 0x6000, 0x7c00, 0x7e00, 0x0300, 0x0100, 0x0100, 0x0300, 0x4300, 0x7e00, 0x0000, 0x0000, 0x0000, 0x0000], // '5'
+  [0x0000, 0x0000, 0x0000, 0x0e00, 0x3f00, 0x2000, 0x6000, 0x6c00, 0x7f00, 0x6100, 0x6180, 0x6180, 0x6100, 0x3300, 0x1e00, 0x0000, 0x0000, 0x0000, 0x0000], // '6'
+  [0x0000, 0x0000, 0x0000, 0x7f00, 0x7f00, 0x0300, 0x0300, 0x0200, 0x0600, 0x0400, 0x0c00, 0x0c00, 0x0800, 0x1800, 0x1000, 0x0000, 0x0000, 0x0000, 0x0000], // '7'
+  [0x0000, 0x0000, 0x0000, 0x1e00, 0x3300, 0x6100, 0x6100, 0x2300, 0x1e00, 0x3f00, 0x6100, 0x6180, 0x6180, 0x6300, 0x3e00, 0x0000, 0x0000, 0x0000, 0x0000], // '8'
+  [0x0000, 0x0000, 0x0000, 0x1c00, 0x3700, 0x6300, 0x6100, 0x6180, 0x6180, 0x7380, 0x3d80, 0x0100, 0x0300, 0x0700, 0x3e00, 0x0000, 0x0000, 0x0000, 0x0000], // '9'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0c00, 0x0c00, 0x0000, 0x0000, 0x0000, 0x0800, 0x0c00, 0x0c00, 0x0000, 0x0000, 0x0000, 0x0000], // ':'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0c00, 0x0c00, 0x0000, 0x0000, 0x0000, 0x0000, 0x0c00, 0x0c00, 0x0800, 0x1800, 0x0000, 0x0000], // ';'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0080, 0x0780, 0x1c00, 0x7000, 0x7000, 0x1c00, 0x0780, 0x0080, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000], // '<'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x7f80, 0x0000, 0x0000, 0x7f80, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000], // '='
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x4000, 0x7000, 0x1e00, 0x0380, 0x0380, 0x1e00, 0x7000, 0x4000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000], // '>'
+  [0x0000, 0x0000, 0x0000, 0x1e00, 0x3f00, 0x0300, 0x0300, 0x0300, 0x0600, 0x0c00, 0x0800, 0x0800, 0x0000, 0x0c00, 0x0c00, 0x0000, 0x0000, 0x0000, 0x0000], // '?'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x1e00, 0x3180, 0x6080, 0x4680, 0xcf80, 0xd880, 0xd880, 0xd880, 0xc980, 0x4f80, 0x4000, 0x2000, 0x1f00, 0x0600, 0x0000], // '@'
+  [0x0000, 0x0000, 0x0000, 0x0c00, 0x1c00, 0x1c00, 0x1600, 0x1200, 0x3200, 0x3300, 0x2300, 0x7f00, 0x6180, 0x4180, 0xc080, 0x0000, 0x0000, 0x0000, 0x0000], // 'A'
+  [0x0000, 0x0000, 0x0000, 0x7c00, 0x7f00, 0x6100, 0x6180, 0x6300, 0x7e00, 0x7f00, 0x6180, 0x6180, 0x6180, 0x6380, 0x7e00, 0x0000, 0x0000, 0x0000, 0x0000], // 'B'
+  [0x0000, 0x0000, 0x0000, 0x0f00, 0x1f80, 0x3000, 0x6000, 0x6000, 0x6000, 0x6000, 0x6000, 0x6000, 0x2000, 0x3100, 0x1f00, 0x0000, 0x0000, 0x0000, 0x0000], // 'C'
+  [0x0000, 0x0000, 0x0000, 0x7800, 0x7e00, 0x6300, 0x6100, 0x6180, 0x6180, 0x6180, 0x6180, 0x6100, 0x6300, 0x6600, 0x7c00, 0x0000, 0x0000, 0x0000, 0x0000], // 'D'
+  [0x0000, 0x0000, 0x0000, 0x3f00, 0x7f00, 0x6000, 0x6000, 0x6000, 0x7f00, 0x7f00, 0x6000, 0x6000, 0x6000, 0x6000, 0x7f80, 0x0000, 0x0000, 0x0000, 0x0000], // 'E'
+  [0x0000, 0x0000, 0x0000, 0x3f80, 0x3f80, 0x3000, 0x3000, 0x3000, 0x3f00, 0x3f00, 0x3000, 0x3000, 0x3000, 0x3000, 0x3000, 0x0000, 0x0000, 0x0000, 0x0000], // 'F'
+  [0x0000, 0x0000, 0x0000, 0x0e00, 0x3f00, 0x2000, 0x6000, 0x6000, 0x6000, 0x6780, 0x6180, 0x6180, 0x6180, 0x3180, 0x1f00, 0x0000, 0x0000, 0x0000, 0x0000], // 'G'
+  [0x0000, 0x0000, 0x0000, 0x6100, 0x6180, 0x6180, 0x6180, 0x6180, 0x7f80, 0x7f80, 0x6180, 0x6180, 0x6180, 0x6180, 0x6180, 0x0000, 0x0000, 0x0000, 0x0000], // 'H'
+  [0x0000, 0x0000, 0x0000, 0x3f00, 0x3f00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x7f00, 0x0000, 0x0000, 0x0000, 0x0000], // 'I'
+  [0x0000, 0x0000, 0x0000, 0x1e00, 0x1f00, 0x0300, 0x0300, 0x0300, 0x0300, 0x0300, 0x0300, 0x0300, 0x0200, 0x4600, 0x7c00, 0x0000, 0x0000, 0x0000, 0x0000], // 'J'
+  [0x0000, 0x0000, 0x0000, 0x6180, 0x6300, 0x6200, 0x6400, 0x6800, 0x7800, 0x7c00, 0x6600, 0x6600, 0x6300, 0x6180, 0x6180, 0x0000, 0x0000, 0x0000, 0x0000], // 'K'
+  [0x0000, 0x0000, 0x0000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x2000, 0x3f80, 0x0000, 0x0000, 0x0000, 0x0000], // 'L'
+  [0x0000, 0x0000, 0x0000, 0x6180, 0x6380, 0x7380, 0x5380, 0x5780, 0x5d80, 0x4d80, 0x4980, 0x4180, 0x4180, 0x4180, 0x4180, 0x0000, 0x0000, 0x0000, 0x0000], // 'M'
+  [0x0000, 0x0000, 0x0000, 0x6100, 0x7180, 0x7180, 0x7180, 0x7980, 0x6980, 0x6d80, 0x6580, 0x6580, 0x6780, 0x6380, 0x6380, 0x0000, 0x0000, 0x0000, 0x0000], // 'N'
+  [0x0000, 0x0000, 0x0000, 0x1e00, 0x3f00, 0x6300, 0x6180, 0x6180, 0x6180, 0x6180, 0x6180, 0x6180, 0x6100, 0x3300, 0x1e00, 0x0000, 0x0000, 0x0000, 0x0000], // 'O'
+  [0x0000, 0x0000, 0x0000, 0x3c00, 0x7f00, 0x6180, 0x6180, 0x6180, 0x6180, 0x7f00, 0x6000, 0x6000, 0x6000, 0x6000, 0x6000, 0x0000, 0x0000, 0x0000, 0x0000], // 'P'
+  [0x0000, 0x0000, 0x0000, 0x1e00, 0x3f00, 0x6300, 0x6180, 0x6180, 0x6180, 0x6180, 0x6180, 0x6180, 0x6100, 0x3300, 0x1e00, 0x0600, 0x0300, 0x0000, 0x0000], // 'Q'
+  [0x0000, 0x0000, 0x0000, 0x7c00, 0x7f00, 0x6300, 0x6100, 0x6100, 0x6300, 0x7e00, 0x6600, 0x6300, 0x6100, 0x6180, 0x6080, 0x0000, 0x0000, 0x0000, 0x0000], // 'R'
+  [0x0000, 0x0000, 0x0000, 0x1e00, 0x3f00, 0x6000, 0x6000, 0x6000, 0x3c00, 0x0f00, 0x0300, 0x0180, 0x0180, 0x6300, 0x7e00, 0x0000, 0x0000, 0x0000, 0x0000], // 'S'
+  [0x0000, 0x0000, 0x0000, 0xff80, 0xff80, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0000, 0x0000, 0x0000, 0x0000], // 'T'
+  [0x0000, 0x0000, 0x0000, 0x6100, 0x6180, 0x6180, 0x6180, 0x6180, 0x6180, 0x6180, 0x6180, 0x6180, 0x6100, 0x2300, 0x3e00, 0x0000, 0x0000, 0x0000, 0x0000], // 'U'
+  [0x0000, 0x0000, 0x0000, 0x4080, 0x4180, 0x6180, 0x6100, 0x2300, 0x2300, 0x3200, 0x1200, 0x1600, 0x1e00, 0x1c00, 0x0c00, 0x0000, 0x0000, 0x0000, 0x0000], // 'V'
+  [0x0000, 0x0000, 0x0000, 0x8080, 0xc0c0, 0xc080, 0xc880, 0x4c80, 0x4d80, 0x5d80, 0x5780, 0x7300, 0x7300, 0x7300, 0x2300, 0x0000, 0x0000, 0x0000, 0x0000], // 'W'
+  [0x0000, 0x0000, 0x0000, 0x4180, 0x6180, 0x3300, 0x1200, 0x1e00, 0x0c00, 0x0c00, 0x1e00, 0x3200, 0x2300, 0x6180, 0xc180, 0x0000, 0x0000, 0x0000, 0x0000], // 'X'
+  [0x0000, 0x0000, 0x0000, 0x4080, 0x6180, 0x6300, 0x3300, 0x1600, 0x1c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0000, 0x0000, 0x0000, 0x0000], // 'Y'
+  [0x0000, 0x0000, 0x0000, 0x3f80, 0x3f80, 0x0100, 0x0300, 0x0600, 0x0400, 0x0c00, 0x1800, 0x1000, 0x3000, 0x6000, 0x7f80, 0x0000, 0x0000, 0x0000, 0x0000], // 'Z'
+  [0x0000, 0x0000, 0x0000, 0x1e00, 0x1800, 0x1800, 0x1800, 0x1800, 0x1800, 0x1800, 0x1800, 0x1800, 0x1800, 0x1800, 0x1800, 0x1800, 0x1e00, 0x0000, 0x0000], // '['
+  [0x0000, 0x0000, 0x0000, 0x4000, 0x6000, 0x2000, 0x3000, 0x1000, 0x1800, 0x0800, 0x0c00, 0x0400, 0x0600, 0x0600, 0x0300, 0x0300, 0x0100, 0x0000, 0x0000], // '\\'
+  [0x0000, 0x0000, 0x0000, 0x1c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x1c00, 0x0000, 0x0000], // ']'
+  [0x0000, 0x0000, 0x0000, 0x0c00, 0x1c00, 0x3200, 0x2100, 0x4080, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000], // '^'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0xff80], // '_'
+  [0x0000, 0x0000, 0x1000, 0x1800, 0x0800, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000], // '`'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x3e00, 0x2300, 0x0100, 0x0f00, 0x3f00, 0x6100, 0x6300, 0x6300, 0x3d00, 0x0000, 0x0000, 0x0000, 0x0000], // 'a'
+  [0x0000, 0x0000, 0x0000, 0x6000, 0x6000, 0x6000, 0x6e00, 0x7300, 0x6100, 0x6180, 0x6180, 0x6180, 0x6100, 0x7300, 0x7e00, 0x0000, 0x0000, 0x0000, 0x0000], // 'b'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0f00, 0x3900, 0x3000, 0x2000, 0x6000, 0x2000, 0x2000, 0x3000, 0x1f00, 0x0000, 0x0000, 0x0000, 0x0000], // 'c'
+  [0x0000, 0x0000, 0x0000, 0x0100, 0x0100, 0x0100, 0x1d00, 0x3700, 0x6300, 0x6100, 0x6100, 0x6100, 0x6300, 0x2300, 0x3d00, 0x0000, 0x0000, 0x0000, 0x0000], // 'd'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x1e00, 0x3300, 0x6100, 0x6180, 0x7f80, 0x6000, 0x6000, 0x3100, 0x1f00, 0x0000, 0x0000, 0x0000, 0x0000], // 'e'
+  [0x0000, 0x0000, 0x0000, 0x0700, 0x0c00, 0x0800, 0x3f00, 0x0800, 0x0800, 0x0800, 0x0800, 0x0800, 0x0800, 0x0800, 0x0800, 0x0000, 0x0000, 0x0000, 0x0000], // 'f'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x1d00, 0x3700, 0x6300, 0x6100, 0x6100, 0x6100, 0x6300, 0x3300, 0x3d00, 0x0100, 0x0300, 0x3e00, 0x0800], // 'g'
+  [0x0000, 0x0000, 0x0000, 0x6000, 0x6000, 0x6000, 0x6e00, 0x7300, 0x6100, 0x6100, 0x6100, 0x6100, 0x6100, 0x6100, 0x6100, 0x0000, 0x0000, 0x0000, 0x0000], // 'h'
+  [0x0000, 0x0000, 0x0000, 0x0c00, 0x0c00, 0x0000, 0x3c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x7f80, 0x0000, 0x0000, 0x0000, 0x0000], // 'i'
+  [0x0000, 0x0000, 0x0000, 0x0400, 0x0400, 0x0000, 0x3c00, 0x0400, 0x0400, 0x0400, 0x0400, 0x0400, 0x0400, 0x0400, 0x0400, 0x0400, 0x0c00, 0x7800, 0x0000], // 'j'
+  [0x0000, 0x0000, 0x0000, 0x2000, 0x2000, 0x2000, 0x2100, 0x2300, 0x2600, 0x3c00, 0x3c00, 0x2600, 0x2300, 0x2100, 0x2180, 0x0000, 0x0000, 0x0000, 0x0000], // 'k'
+  [0x0000, 0x0000, 0x0000, 0x7800, 0x1800, 0x1800, 0x1800, 0x1800, 0x1800, 0x1800, 0x1800, 0x1800, 0x0800, 0x0c00, 0x0700, 0x0000, 0x0000, 0x0000, 0x0000], // 'l'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x7b00, 0x6d80, 0x4d80, 0x4d80, 0x4d80, 0x4d80, 0x4d80, 0x4d80, 0x4d80, 0x0000, 0x0000, 0x0000, 0x0000], // 'm'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x2e00, 0x7300, 0x6100, 0x6100, 0x6100, 0x6100, 0x6100, 0x6100, 0x6100, 0x0000, 0x0000, 0x0000, 0x0000], // 'n'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x1e00, 0x3300, 0x6100, 0x6180, 0x6180, 0x6180, 0x6100, 0x3300, 0x3e00, 0x0000, 0x0000, 0x0000, 0x0000], // 'o'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x2e00, 0x7300, 0x6100, 0x6180, 0x6180, 0x6180, 0x6100, 0x7300, 0x7e00, 0x6000, 0x6000, 0x6000, 0x0000], // 'p'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x1d00, 0x3700, 0x6300, 0x6100, 0x6100, 0x6100, 0x6100, 0x2300, 0x3f00, 0x0100, 0x0100, 0x0100, 0x0000], // 'q'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x1780, 0x1c80, 0x1800, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x1000, 0x0000, 0x0000, 0x0000, 0x0000], // 'r'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x1e00, 0x3000, 0x2000, 0x3000, 0x1e00, 0x0300, 0x0300, 0x0300, 0x3e00, 0x0000, 0x0000, 0x0000, 0x0000], // 's'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x1800, 0x1800, 0x7f00, 0x1800, 0x1800, 0x1800, 0x1800, 0x1800, 0x1800, 0x0800, 0x0f00, 0x0000, 0x0000, 0x0000, 0x0000], // 't'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x2100, 0x6100, 0x6100, 0x6100, 0x6100, 0x6100, 0x6100, 0x3300, 0x3d00, 0x0000, 0x0000, 0x0000, 0x0000], // 'u'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x4180, 0x6100, 0x2100, 0x2300, 0x3200, 0x1200, 0x1600, 0x1c00, 0x0c00, 0x0000, 0x0000, 0x0000, 0x0000], // 'v'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x8080, 0xc080, 0xc080, 0x4d80, 0x4d80, 0x7500, 0x7700, 0x3300, 0x3300, 0x0000, 0x0000, 0x0000, 0x0000], // 'w'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x6100, 0x2300, 0x1600, 0x1c00, 0x0c00, 0x1c00, 0x3200, 0x2300, 0x6180, 0x0000, 0x0000, 0x0000, 0x0000], // 'x'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x4180, 0x6180, 0x2100, 0x3300, 0x3200, 0x1200, 0x1e00, 0x0c00, 0x0c00, 0x0800, 0x1800, 0x7000, 0x0000], // 'y'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x3f00, 0x3f00, 0x0200, 0x0600, 0x0c00, 0x1800, 0x3000, 0x3000, 0x7f00, 0x0000, 0x0000, 0x0000, 0x0000], // 'z'
+  [0x0000, 0x0000, 0x0000, 0x0700, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x1800, 0x3800, 0x0800, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0f00, 0x0300, 0x0000], // '{'
+  [0x0000, 0x0000, 0x0000, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0800], // '|'
+  [0x0000, 0x0000, 0x0000, 0x3800, 0x0800, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0e00, 0x0700, 0x0c00, 0x0c00, 0x0c00, 0x0c00, 0x0800, 0x3800, 0x3000, 0x0000], // '}'
+  [0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x7880, 0x4f80, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000, 0x0000], // '~'
+  [0x0000, 0x0000, 0x0000, 0x7f80, 0x4080, 0x4080, 0x4080, 0x4080, 0x4080, 0x4080, 0x4080, 0x4080, 0x4080, 0x4080, 0x7f80, 0x0000, 0x0000, 0x0000, 0x0000], // replacement
+];
+
+#[cfg(test)]
+mod tests {
+  use super::Font;
+
+  #[test]
+  fn advances_scale_with_font_size() {
+    let font = Font::bundled();
+    assert_eq!(font.advance('a', 16.0), 10.0);
+    assert_eq!(font.advance('W', 32.0), 20.0);
+    assert_eq!(font.measure("hello", 8.0), 25.0);
+    assert_eq!(font.measure("", 16.0), 0.0);
+  }
+
+  #[test]
+  fn vertical_metrics_scale_with_font_size() {
+    let font = Font::bundled();
+    let metrics = font.metrics(16.0);
+    assert_eq!((metrics.ascent, metrics.descent, metrics.line_height), (15.0, 4.0, 19.0));
+
+    let metrics = font.metrics(32.0);
+    assert_eq!((metrics.ascent, metrics.descent, metrics.line_height), (30.0, 8.0, 38.0));
+  }
+
+  #[test]
+  fn unmapped_chars_get_the_replacement_glyph() {
+    let font = Font::bundled();
+    let replacement = font.glyph('\u{2603}');
+    let pixels = |c: char| {
+      let glyph = font.glyph(c);
+      (0..glyph.height()).map(|y| (0..glyph.width()).filter(|&x| glyph.is_set(x, y)).count()).collect::<Vec<usize>>()
+    };
+
+    // a hollow box, unlike any printable char
+    assert!(replacement.is_set(1, 3) && !replacement.is_set(4, 8));
+    assert_eq!(pixels('\u{e9}'), pixels('\u{2603}'));
+    assert_ne!(pixels('~'), pixels('\u{2603}'));
+    assert!(pixels(' ').iter().all(|&count| count == 0));
+    // unmapped chars still advance like any other
+    assert_eq!(font.advance('\u{2603}', 16.0), 10.0);
+  }
+}
@@ -0,0 +1,1647 @@
+//! HTML tree builder, consuming tokens from `tokenizer`
+//! (a subset of the WHATWG tree construction rules: the
+//! insertion modes up to "after after body", implied end tags,
+//! the adoption agency algorithm for misnested formatting
+//! elements, void elements and raw text elements, but no table
+//! insertion modes, foster parenting or breaking out of foreign
+//! content)
+
+use std::collections::HashMap;
+use crate::parser::ParseError;
+use crate::tokenizer::{is_space, Doctype, State, Tag, Token, Tokenizer};
+use crate::dom;
+use crate::css;
+
+// a node in the tree being built; children are indexes into
+// `Parser::nodes`, so nodes can be moved around before the
+// tree is turned into a dom::Node
+struct TreeNode {
+  node_type: dom::NodeType,
+  parent: Option<usize>,
+  children: Vec<usize>,
+  // is this an SVG or MathML element (or inside one)?
+  foreign: bool,
+}
+
+// the document node is always the first in `nodes`
+const DOCUMENT: usize = 0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum InsertionMode {
+  Initial,
+  BeforeHtml,
+  BeforeHead,
+  InHead,
+  AfterHead,
+  InBody,
+  Text,
+  AfterBody,
+  AfterAfterBody,
+}
+
+// an entry in the list of active formatting elements
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Formatting {
+  Marker,
+  Element(usize),
+}
+
+// the kinds of "has an element in scope" checks
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Scope {
+  Default,
+  ListItem,
+  Button,
+  Table,
+}
+
+// elements that end the default scope
+const SCOPE_BOUNDARIES: &[&str] = &[
+  "applet", "caption", "html", "table", "td", "th", "marquee", "object", "template",
+];
+
+// elements that can be closed without an end tag, by
+// `generate_implied_end_tags`
+const IMPLIED_END_TAGS: &[&str] = &[
+  "dd", "dt", "li", "optgroup", "option", "p", "rb", "rp", "rt", "rtc",
+];
+
+// the "special" category of the spec, which stops the search
+// for an element to close and the adoption agency algorithm
+const SPECIAL: &[&str] = &[
+  "address", "applet", "area", "article", "aside", "base", "basefont", "bgsound",
+  "blockquote", "body", "br", "button", "caption", "center", "col", "colgroup", "dd",
+  "details", "dir", "div", "dl", "dt", "embed", "fieldset", "figcaption", "figure",
+  "footer", "form", "frame", "frameset", "h1", "h2", "h3", "h4", "h5", "h6", "head",
+  "header", "hgroup", "hr", "html", "iframe", "img", "input", "keygen", "li", "link",
+  "listing", "main", "marquee", "menu", "meta", "nav", "noembed", "noframes",
+  "noscript", "object", "ol", "p", "param", "plaintext", "pre", "script", "search",
+  "section", "select", "source", "style", "summary", "table", "tbody", "td",
+  "template", "textarea", "tfoot", "th", "thead", "title", "tr", "track", "ul", "wbr",
+  "xmp",
+];
+
+// block-level elements whose start tag closes an open <p>
+const CLOSES_P: &[&str] = &[
+  "address", "article", "aside", "blockquote", "center", "details", "dialog", "dir",
+  "div", "dl", "fieldset", "figcaption", "figure", "footer", "header", "hgroup",
+  "main", "menu", "nav", "ol", "p", "search", "section", "summary", "ul",
+];
+
+// elements closed by their end tag if it's in scope
+const CLOSED_IN_SCOPE: &[&str] = &[
+  "address", "article", "aside", "blockquote", "button", "center", "details",
+  "dialog", "dir", "div", "dl", "fieldset", "figcaption", "figure", "footer", "form",
+  "header", "hgroup", "listing", "main", "menu", "nav", "ol", "pre", "search",
+  "section", "summary", "ul",
+];
+
+// elements that never have contents, and so no end tag
+const VOID_ELEMENTS: &[&str] = &[
+  "area", "base", "basefont", "bgsound", "br", "col", "embed", "frame", "hr", "img",
+  "input", "keygen", "link", "meta", "param", "source", "track", "wbr",
+];
+
+const HEADINGS: &[&str] = &["h1", "h2", "h3", "h4", "h5", "h6"];
+
+const FORMATTING: &[&str] = &[
+  "a", "b", "big", "code", "em", "font", "i", "nobr", "s", "small", "strike", "strong",
+  "tt", "u",
+];
+
+// elements only allowed inside tables
+const TABLE_PARTS: &[&str] = &[
+  "caption", "col", "colgroup", "tbody", "td", "tfoot", "th", "thead", "tr",
+];
+
+const TABLE_SECTIONS: &[&str] = &["tbody", "tfoot", "thead"];
+
+// start tags that "in head" handles, also when they appear later
+const HEAD_CONTENT: &[&str] = &[
+  "base", "basefont", "bgsound", "link", "meta", "noframes", "script", "style", "title",
+];
+
+// public identifiers of doctypes that put the document in quirks
+// mode, matched as ASCII case-insensitive prefixes
+const QUIRKS_PUBLIC_PREFIXES: &[&str] = &[
+  "+//silmaril//dtd html pro v0r11 19970101//",
+  "-//as//dtd html 3.0 aswedit + extensions//",
+  "-//advasoft ltd//dtd html 3.0 aswedit + extensions//",
+  "-//ietf//dtd html 2.0 level 1//",
+  "-//ietf//dtd html 2.0 level 2//",
+  "-//ietf//dtd html 2.0 strict level 1//",
+  "-//ietf//dtd html 2.0 strict level 2//",
+  "-//ietf//dtd html 2.0 strict//",
+  "-//ietf//dtd html 2.0//",
+  "-//ietf//dtd html 2.1e//",
+  "-//ietf//dtd html 3.0//",
+  "-//ietf//dtd html 3.2 final//",
+  "-//ietf//dtd html 3.2//",
+  "-//ietf//dtd html 3//",
+  "-//ietf//dtd html level 0//",
+  "-//ietf//dtd html level 1//",
+  "-//ietf//dtd html level 2//",
+  "-//ietf//dtd html level 3//",
+  "-//ietf//dtd html strict level 0//",
+  "-//ietf//dtd html strict level 1//",
+  "-//ietf//dtd html strict level 2//",
+  "-//ietf//dtd html strict level 3//",
+  "-//ietf//dtd html strict//",
+  "-//ietf//dtd html//",
+  "-//metrius//dtd metrius presentational//",
+  "-//microsoft//dtd internet explorer 2.0 html strict//",
+  "-//microsoft//dtd internet explorer 2.0 html//",
+  "-//microsoft//dtd internet explorer 2.0 tables//",
+  "-//microsoft//dtd internet explorer 3.0 html strict//",
+  "-//microsoft//dtd internet explorer 3.0 html//",
+  "-//microsoft//dtd internet explorer 3.0 tables//",
+  "-//netscape comm. corp.//dtd html//",
+  "-//netscape comm. corp.//dtd strict html//",
+  "-//o'reilly and associates//dtd html 2.0//",
+  "-//o'reilly and associates//dtd html extended 1.0//",
+  "-//o'reilly and associates//dtd html extended relaxed 1.0//",
+  "-//sq//dtd html 2.0 hotmetal + extensions//",
+  "-//softquad software//dtd hotmetal pro 6.0::19990601::extensions to html 4.0//",
+  "-//softquad//dtd hotmetal pro 4.0::19971010::extensions to html 4.0//",
+  "-//spyglass//dtd html 2.0 extended//",
+  "-//sun microsystems corp.//dtd hotjava html//",
+  "-//sun microsystems corp.//dtd hotjava strict html//",
+  "-//w3c//dtd html 3 1995-03-24//",
+  "-//w3c//dtd html 3.2 draft//",
+  "-//w3c//dtd html 3.2 final//",
+  "-//w3c//dtd html 3.2//",
+  "-//w3c//dtd html 3.2s draft//",
+  "-//w3c//dtd html 4.0 frameset//",
+  "-//w3c//dtd html 4.0 transitional//",
+  "-//w3c//dtd html experimental 19960712//",
+  "-//w3c//dtd html experimental 970421//",
+  "-//w3c//dtd w3 html//",
+  "-//w3o//dtd w3 html 3.0//",
+  "-//webtechs//dtd mozilla html 2.0//",
+  "-//webtechs//dtd mozilla html//",
+];
+
+// public identifiers that mean quirks mode without a system
+// identifier, and limited-quirks mode with one
+const HTML4_TRANSITIONAL_PREFIXES: &[&str] = &[
+  "-//w3c//dtd html 4.01 frameset//",
+  "-//w3c//dtd html 4.01 transitional//",
+];
+
+const LIMITED_QUIRKS_PUBLIC_PREFIXES: &[&str] = &[
+  "-//w3c//dtd xhtml 1.0 frameset//",
+  "-//w3c//dtd xhtml 1.0 transitional//",
+];
+
+struct Parser {
+  tokenizer: Tokenizer,
+  nodes: Vec<TreeNode>,
+  mode: InsertionMode,
+  // the mode to go back to after a raw text element
+  original_mode: InsertionMode,
+  // stack of open elements, innermost last
+  open_elements: Vec<usize>,
+  active_formatting: Vec<Formatting>,
+  head: Option<usize>,
+  // drop a newline straight after <pre>, <listing> or <textarea>
+  skip_newline: bool,
+  // where the contents of the open raw text element start
+  text_start: usize,
+  document_mode: dom::DocumentMode,
+  // the contents of closed <style> elements, by node
+  parsed_styles: HashMap<usize, css::Stylesheet>,
+  // the stylesheets of all <style> elements, in tree order
+  style_sheets: Vec<css::Stylesheet>,
+}
+
+impl Parser {
+  // consume tokens up to EOF
+  fn run(&mut self) {
+    loop {
+      let token = self.tokenizer.next_token();
+      if self.skip_newline {
+        self.skip_newline = false;
+        if token == Token::Character('\n') {
+          continue;
+        }
+      }
+
+      // `/>` only closes void elements and foreign elements
+      if let Token::StartTag(ref tag) = token {
+        let foreign = self.nodes[self.current_node()].foreign || tag.name == "svg" || tag.name == "math";
+        if tag.self_closing && !VOID_ELEMENTS.contains(&tag.name.as_str()) && !foreign {
+          let start = self.tokenizer.token_start;
+          self.tokenizer.warn_at(start, "`>` after a non-void element", "`/>`");
+        }
+      }
+
+      let eof = token == Token::Eof;
+      self.process(token);
+      if eof { break }
+    }
+
+    // close anything still open
+    while !self.open_elements.is_empty() {
+      self.pop();
+    }
+  }
+
+  // handle `token` according to the current insertion mode
+  fn process(&mut self, token: Token) {
+    match self.mode {
+      InsertionMode::Initial => self.initial(token),
+      InsertionMode::BeforeHtml => self.before_html(token),
+      InsertionMode::BeforeHead => self.before_head(token),
+      InsertionMode::InHead => self.in_head(token),
+      InsertionMode::AfterHead => self.after_head(token),
+      InsertionMode::InBody => self.in_body(token),
+      InsertionMode::Text => self.text(token),
+      InsertionMode::AfterBody => self.after_body(token),
+      InsertionMode::AfterAfterBody => self.after_after_body(token),
+    }
+  }
+
+  // switch to `mode` and handle `token` again there
+  fn reprocess(&mut self, mode: InsertionMode, token: Token) {
+    self.mode = mode;
+    self.process(token);
+  }
+
+  fn initial(&mut self, token: Token) {
+    match token {
+      Token::Character(c) if is_space(c) => {}
+      Token::Comment(comment) => self.insert_comment(comment, DOCUMENT),
+      Token::Doctype(doctype) => {
+        let name = doctype.name.clone().unwrap_or_default();
+        let legacy_system_id = doctype.system_id.as_ref().is_some_and(|id| id != "about:legacy-compat");
+        if name != "html" || doctype.public_id.is_some() || legacy_system_id {
+          let start = self.tokenizer.token_start;
+          self.tokenizer.warn_at(start, "`<!DOCTYPE html>`", "a legacy doctype");
+        }
+
+        self.document_mode = document_mode(&doctype);
+        let node = self.add_node(dom::NodeType::Doctype(dom::DoctypeData {
+          name,
+          public_id: doctype.public_id.unwrap_or_default(),
+          system_id: doctype.system_id.unwrap_or_default(),
+        }));
+        self.append_child(DOCUMENT, node);
+        self.mode = InsertionMode::BeforeHtml;
+      }
+      token => {
+        // no doctype at all
+        let (start, found) = self.describe_token(&token);
+        self.tokenizer.warn_at(start, "`<!DOCTYPE html>`", &found);
+        self.document_mode = dom::DocumentMode::Quirks;
+        self.reprocess(InsertionMode::BeforeHtml, token);
+      }
+    }
+  }
+
+  fn before_html(&mut self, token: Token) {
+    match token {
+      Token::Doctype(_) => self.unexpected_doctype(),
+      Token::Comment(comment) => self.insert_comment(comment, DOCUMENT),
+      Token::Character(c) if is_space(c) => {}
+      Token::StartTag(ref tag) if tag.name == "html" => {
+        let node = self.create_element(tag);
+        self.append_child(DOCUMENT, node);
+        self.open_elements.push(node);
+        self.mode = InsertionMode::BeforeHead;
+      }
+      Token::EndTag(ref tag) if !matches!(tag.name.as_str(), "head" | "body" | "html" | "br") => {
+        self.unexpected_end_tag(&tag.name);
+      }
+      token => {
+        let node = self.create_element(&implied_tag("html"));
+        self.append_child(DOCUMENT, node);
+        self.open_elements.push(node);
+        self.reprocess(InsertionMode::BeforeHead, token);
+      }
+    }
+  }
+
+  fn before_head(&mut self, token: Token) {
+    match token {
+      Token::Character(c) if is_space(c) => {}
+      Token::Comment(comment) => self.insert_comment_here(comment),
+      Token::Doctype(_) => self.unexpected_doctype(),
+      Token::StartTag(ref tag) if tag.name == "html" => self.in_body(token),
+      Token::StartTag(ref tag) if tag.name == "head" => {
+        self.head = Some(self.insert_element(tag));
+        self.mode = InsertionMode::InHead;
+      }
+      Token::EndTag(ref tag) if !matches!(tag.name.as_str(), "head" | "body" | "html" | "br") => {
+        self.unexpected_end_tag(&tag.name);
+      }
+      token => {
+        self.head = Some(self.insert_element(&implied_tag("head")));
+        self.reprocess(InsertionMode::InHead, token);
+      }
+    }
+  }
+
+  fn in_head(&mut self, token: Token) {
+    match token {
+      Token::Character(c) if is_space(c) => self.insert_character(c),
+      Token::Comment(comment) => self.insert_comment_here(comment),
+      Token::Doctype(_) => self.unexpected_doctype(),
+      Token::StartTag(ref tag) if tag.name == "html" => self.in_body(token),
+      Token::StartTag(ref tag) if matches!(tag.name.as_str(), "base" | "basefont" | "bgsound" | "link" | "meta") => {
+        self.insert_void_element(tag);
+      }
+      Token::StartTag(ref tag) if tag.name == "title" => self.insert_text_element(tag, State::Rcdata),
+      // there are no script data escapes, so scripts are plain raw text
+      Token::StartTag(ref tag) if matches!(tag.name.as_str(), "noframes" | "script" | "style") => {
+        self.insert_text_element(tag, State::Rawtext);
+      }
+      Token::EndTag(ref tag) if tag.name == "head" => {
+        self.pop();
+        self.mode = InsertionMode::AfterHead;
+      }
+      Token::StartTag(ref tag) if tag.name == "head" => self.unexpected_start_tag(&tag.name),
+      Token::EndTag(ref tag) if !matches!(tag.name.as_str(), "body" | "html" | "br") => {
+        self.unexpected_end_tag(&tag.name);
+      }
+      token => {
+        self.pop();
+        self.reprocess(InsertionMode::AfterHead, token);
+      }
+    }
+  }
+
+  fn after_head(&mut self, token: Token) {
+    match token {
+      Token::Character(c) if is_space(c) => self.insert_character(c),
+      Token::Comment(comment) => self.insert_comment_here(comment),
+      Token::Doctype(_) => self.unexpected_doctype(),
+      Token::StartTag(ref tag) if tag.name == "html" => self.in_body(token),
+      Token::StartTag(ref tag) if tag.name == "body" => {
+        self.insert_element(tag);
+        self.mode = InsertionMode::InBody;
+      }
+      Token::StartTag(ref tag) if HEAD_CONTENT.contains(&tag.name.as_str()) => {
+        // put it in the head, even though that's been closed
+        self.unexpected_start_tag(&tag.name);
+        let head = self.head.unwrap();
+        self.open_elements.push(head);
+        self.in_head(token);
+        if let Some(index) = self.open_elements.iter().rposition(|&node| node == head) {
+          self.open_elements.remove(index);
+        }
+      }
+      Token::StartTag(ref tag) if tag.name == "head" => self.unexpected_start_tag(&tag.name),
+      Token::EndTag(ref tag) if !matches!(tag.name.as_str(), "body" | "html" | "br") => {
+        self.unexpected_end_tag(&tag.name);
+      }
+      token => {
+        self.insert_element(&implied_tag("body"));
+        self.reprocess(InsertionMode::InBody, token);
+      }
+    }
+  }
+
+  fn in_body(&mut self, token: Token) {
+    match token {
+      Token::Character('\0') => {
+        let start = self.tokenizer.position() - 1;
+        self.tokenizer.warn_at(start, "text", "a null character");
+      }
+      Token::Character(c) => {
+        self.reconstruct_active_formatting_elements();
+        self.insert_character(c);
+      }
+      Token::Comment(comment) => self.insert_comment_here(comment),
+      Token::Doctype(_) => self.unexpected_doctype(),
+      Token::StartTag(tag) => self.in_body_start_tag(tag),
+      Token::EndTag(tag) => self.in_body_end_tag(tag),
+      Token::Eof => {}
+    }
+  }
+
+  fn in_body_start_tag(&mut self, tag: Tag) {
+    let name = tag.name.as_str();
+    match name {
+      "html" => {
+        self.unexpected_start_tag(name);
+        let html = self.open_elements[0];
+        self.add_missing_attributes(html, &tag);
+      }
+      _ if HEAD_CONTENT.contains(&name) => self.in_head(Token::StartTag(tag)),
+      "body" => {
+        self.unexpected_start_tag(name);
+        if let Some(&body) = self.open_elements.get(1) {
+          if self.tag_name(body) == "body" {
+            self.add_missing_attributes(body, &tag);
+          }
+        }
+      }
+      _ if CLOSES_P.contains(&name) => {
+        self.close_p_in_button_scope();
+        self.insert_element(&tag);
+      }
+      _ if HEADINGS.contains(&name) => {
+        self.close_p_in_button_scope();
+        if HEADINGS.contains(&self.tag_name(self.current_node())) {
+          // headings don't nest
+          self.unexpected_start_tag(name);
+          self.pop();
+        }
+        self.insert_element(&tag);
+      }
+      "pre" | "listing" => {
+        self.close_p_in_button_scope();
+        self.insert_element(&tag);
+        self.skip_newline = true;
+      }
+      "form" => {
+        self.close_p_in_button_scope();
+        self.insert_element(&tag);
+      }
+      "li" | "dd" | "dt" => {
+        // close the previous list item, unless something
+        // other than a <div>, <p> or <address> is in the way
+        let closes: &[&str] = if name == "li" { &["li"] } else { &["dd", "dt"] };
+        for index in (0..self.open_elements.len()).rev() {
+          let node_name = self.tag_name(self.open_elements[index]).to_string();
+          if closes.contains(&node_name.as_str()) {
+            self.generate_implied_end_tags(Some(&node_name));
+            if self.tag_name(self.current_node()) != node_name {
+              self.unexpected_start_tag(name);
+            }
+            self.pop_until(&[node_name.as_str()]);
+            break;
+          }
+          if SPECIAL.contains(&node_name.as_str()) && !matches!(node_name.as_str(), "address" | "div" | "p") {
+            break;
+          }
+        }
+        self.close_p_in_button_scope();
+        self.insert_element(&tag);
+      }
+      "textarea" => {
+        self.insert_text_element(&tag, State::Rcdata);
+        self.skip_newline = true;
+      }
+      "xmp" => {
+        self.close_p_in_button_scope();
+        self.reconstruct_active_formatting_elements();
+        self.insert_text_element(&tag, State::Rawtext);
+      }
+      "iframe" | "noembed" => self.insert_text_element(&tag, State::Rawtext),
+      "plaintext" => {
+        self.close_p_in_button_scope();
+        self.insert_element(&tag);
+        self.tokenizer.switch_to(State::Plaintext);
+      }
+      "button" => {
+        if self.has_in_scope(&["button"], Scope::Default) {
+          self.unexpected_start_tag(name);
+          self.generate_implied_end_tags(None);
+          self.pop_until(&["button"]);
+        }
+        self.reconstruct_active_formatting_elements();
+        self.insert_element(&tag);
+      }
+      "a" => {
+        let open_a = self.active_formatting.iter().rev()
+          .take_while(|&&entry| entry != Formatting::Marker)
+          .any(|&entry| matches!(entry, Formatting::Element(node) if self.tag_name(node) == "a"));
+        if open_a {
+          // an <a> can't contain another: close the open one first
+          self.unexpected_start_tag(name);
+          let a = self.formatting_element("a");
+          self.adoption_agency("a");
+          if let Some(a) = a {
+            self.remove_from_active_formatting(a);
+            self.open_elements.retain(|&node| node != a);
+          }
+        }
+        self.reconstruct_active_formatting_elements();
+        let node = self.insert_element(&tag);
+        self.push_active_formatting(node);
+      }
+      "nobr" => {
+        self.reconstruct_active_formatting_elements();
+        if self.has_in_scope(&["nobr"], Scope::Default) {
+          self.unexpected_start_tag(name);
+          self.adoption_agency("nobr");
+          self.reconstruct_active_formatting_elements();
+        }
+        let node = self.insert_element(&tag);
+        self.push_active_formatting(node);
+      }
+      _ if FORMATTING.contains(&name) => {
+        self.reconstruct_active_formatting_elements();
+        let node = self.insert_element(&tag);
+        self.push_active_formatting(node);
+      }
+      "applet" | "marquee" | "object" => {
+        self.reconstruct_active_formatting_elements();
+        self.insert_element(&tag);
+        self.active_formatting.push(Formatting::Marker);
+      }
+      "table" => {
+        // quirks mode lets tables sit inside paragraphs
+        if self.document_mode != dom::DocumentMode::Quirks {
+          self.close_p_in_button_scope();
+        }
+        self.insert_element(&tag);
+      }
+      "option" | "optgroup" => {
+        if self.tag_name(self.current_node()) == "option" {
+          self.pop();
+        }
+        self.reconstruct_active_formatting_elements();
+        self.insert_element(&tag);
+      }
+      _ if TABLE_PARTS.contains(&name) => self.table_part_start_tag(tag),
+      "area" | "br" | "embed" | "img" | "keygen" | "wbr" | "input" => {
+        self.reconstruct_active_formatting_elements();
+        self.insert_void_element(&tag);
+      }
+      "param" | "source" | "track" => self.insert_void_element(&tag),
+      "hr" => {
+        self.close_p_in_button_scope();
+        self.insert_void_element(&tag);
+      }
+      "image" => {
+        // an old alias for <img>
+        self.unexpected_start_tag(name);
+        let tag = Tag { name: "img".to_string(), ..tag };
+        self.in_body_start_tag(tag);
+      }
+      "head" | "frame" => self.unexpected_start_tag(name),
+      _ => {
+        self.reconstruct_active_formatting_elements();
+        let node = self.insert_element(&tag);
+        if tag.self_closing && self.nodes[node].foreign {
+          self.pop();
+        }
+      }
+    }
+  }
+
+  // start tags for the parts of a table, which insert any row
+  // or section they imply (a simplified version of the table
+  // insertion modes, with no foster parenting)
+  fn table_part_start_tag(&mut self, tag: Tag) {
+    let name = tag.name.as_str();
+    if !self.has_in_scope(&["table"], Scope::Table) {
+      self.unexpected_start_tag(name);
+      return;
+    }
+
+    if self.has_in_scope(&["td", "th"], Scope::Table) {
+      self.close_cell();
+    }
+    if name == "col" {
+      self.insert_void_element(&tag);
+      return;
+    }
+    if name == "caption" || name == "colgroup" || TABLE_SECTIONS.contains(&name) {
+      // these go straight in the table
+      if self.has_in_scope(&["tr"], Scope::Table) {
+        self.pop_until(&["tr"]);
+      }
+      if self.has_in_scope(TABLE_SECTIONS, Scope::Table) {
+        self.pop_until(TABLE_SECTIONS);
+      }
+      self.insert_element(&tag);
+      if name == "caption" {
+        self.active_formatting.push(Formatting::Marker);
+      }
+      return;
+    }
+
+    if name == "tr" && self.has_in_scope(&["tr"], Scope::Table) {
+      self.pop_until(&["tr"]);
+    }
+    if !self.has_in_scope(TABLE_SECTIONS, Scope::Table) {
+      self.insert_element(&implied_tag("tbody"));
+    }
+    if name != "tr" && !self.has_in_scope(&["tr"], Scope::Table) {
+      self.insert_element(&implied_tag("tr"));
+    }
+    self.insert_element(&tag);
+    if name != "tr" {
+      self.active_formatting.push(Formatting::Marker);
+    }
+  }
+
+  // close the open <td> or <th>
+  fn close_cell(&mut self) {
+    self.generate_implied_end_tags(None);
+    self.pop_until(&["td", "th"]);
+    self.clear_active_formatting_to_marker();
+  }
+
+  fn in_body_end_tag(&mut self, tag: Tag) {
+    let name = tag.name.as_str();
+    match name {
+      "body" | "html" => {
+        if !self.has_in_scope(&["body"], Scope::Default) {
+          self.unexpected_end_tag(name);
+          return;
+        }
+        self.mode = InsertionMode::AfterBody;
+        if name == "html" {
+          self.process(Token::EndTag(tag));
+        }
+      }
+      _ if CLOSED_IN_SCOPE.contains(&name) => self.close_element_in_scope(name, Scope::Default),
+      "p" => {
+        if !self.has_in_scope(&["p"], Scope::Button) {
+          // `</p>` with no open <p> makes an empty one
+          self.unexpected_end_tag(name);
+          self.insert_element(&implied_tag("p"));
+        }
+        self.close_p();
+      }
+      "li" => self.close_element_in_scope(name, Scope::ListItem),
+      "dd" | "dt" => self.close_element_in_scope(name, Scope::Default),
+      _ if HEADINGS.contains(&name) => {
+        if !self.has_in_scope(HEADINGS, Scope::Default) {
+          self.unexpected_end_tag(name);
+          return;
+        }
+        self.generate_implied_end_tags(None);
+        if self.tag_name(self.current_node()) != name {
+          self.misnested_end_tag(name);
+        }
+        self.pop_until(HEADINGS);
+      }
+      _ if FORMATTING.contains(&name) => self.adoption_agency(name),
+      "br" => {
+        // `</br>` is treated as `<br>`
+        self.unexpected_end_tag(name);
+        self.in_body_start_tag(Tag { attributes: Vec::new(), ..tag });
+      }
+      "applet" | "marquee" | "object" => {
+        if !self.has_in_scope(&[name], Scope::Default) {
+          self.unexpected_end_tag(name);
+          return;
+        }
+        self.generate_implied_end_tags(None);
+        if self.tag_name(self.current_node()) != name {
+          self.misnested_end_tag(name);
+        }
+        self.pop_until(&[name]);
+        self.clear_active_formatting_to_marker();
+      }
+      "table" | "tbody" | "tfoot" | "thead" | "tr" | "td" | "th" | "caption" | "colgroup" => {
+        if !self.has_in_scope(&[name], Scope::Table) {
+          self.unexpected_end_tag(name);
+          return;
+        }
+        self.generate_implied_end_tags(None);
+        if self.tag_name(self.current_node()) != name {
+          self.misnested_end_tag(name);
+        }
+        self.pop_until(&[name]);
+        if matches!(name, "td" | "th" | "caption") {
+          self.clear_active_formatting_to_marker();
+        }
+      }
+      _ => self.any_other_end_tag(name),
+    }
+  }
+
+  // close the innermost open `name`, if it's in `scope`
+  fn close_element_in_scope(&mut self, name: &str, scope: Scope) {
+    if !self.has_in_scope(&[name], scope) {
+      self.unexpected_end_tag(name);
+      return;
+    }
+    self.generate_implied_end_tags(Some(name));
+    if self.tag_name(self.current_node()) != name {
+      self.misnested_end_tag(name);
+    }
+    self.pop_until(&[name]);
+  }
+
+  // an end tag with no special rules closes the innermost open
+  // element with its name, unless a special element is in the way
+  fn any_other_end_tag(&mut self, name: &str) {
+    for index in (0..self.open_elements.len()).rev() {
+      let node = self.open_elements[index];
+      if self.tag_name(node) == name {
+        self.generate_implied_end_tags(Some(name));
+        if self.current_node() != node {
+          self.misnested_end_tag(name);
+        }
+        while self.open_elements.len() > index {
+          self.pop();
+        }
+        return;
+      }
+      if SPECIAL.contains(&self.tag_name(node)) {
+        self.unexpected_end_tag(name);
+        return;
+      }
+    }
+  }
+
+  // the contents of a raw text element
+  fn text(&mut self, token: Token) {
+    match token {
+      Token::Character(c) => self.insert_character(c),
+      Token::Eof => {
+        let expected = format!("`</{}>`", self.tag_name(self.current_node()));
+        let end = self.tokenizer.position();
+        self.tokenizer.warn_at(end, &expected, "end of input");
+        self.pop();
+        self.reprocess(self.original_mode, token);
+      }
+      _ => {
+        // the tokenizer only leaves raw text at the matching end tag
+        self.pop();
+        self.mode = self.original_mode;
+      }
+    }
+  }
+
+  fn after_body(&mut self, token: Token) {
+    match token {
+      Token::Character(c) if is_space(c) => self.in_body(token),
+      Token::Comment(comment) => {
+        let html = self.open_elements[0];
+        self.insert_comment(comment, html);
+      }
+      Token::Doctype(_) => self.unexpected_doctype(),
+      Token::StartTag(ref tag) if tag.name == "html" => self.in_body(token),
+      Token::EndTag(ref tag) if tag.name == "html" => self.mode = InsertionMode::AfterAfterBody,
+      Token::Eof => {}
+      token => {
+        self.unexpected_token(&token);
+        self.reprocess(InsertionMode::InBody, token);
+      }
+    }
+  }
+
+  fn after_after_body(&mut self, token: Token) {
+    match token {
+      Token::Comment(comment) => self.insert_comment(comment, DOCUMENT),
+      Token::Doctype(_) => self.in_body(token),
+      Token::Character(c) if is_space(c) => self.in_body(token),
+      Token::StartTag(ref tag) if tag.name == "html" => self.in_body(token),
+      Token::Eof => {}
+      token => {
+        self.unexpected_token(&token);
+        self.reprocess(InsertionMode::InBody, token);
+      }
+    }
+  }
+
+  // the node new nodes are appended to
+  fn current_node(&self) -> usize {
+    *self.open_elements.last().unwrap_or(&DOCUMENT)
+  }
+
+  fn add_node(&mut self, node_type: dom::NodeType) -> usize {
+    self.nodes.push(TreeNode { node_type, parent: None, children: Vec::new(), foreign: false });
+    self.nodes.len() - 1
+  }
+
+  // move `child` to the end of `parent`'s children
+  fn append_child(&mut self, parent: usize, child: usize) {
+    self.detach(child);
+    self.nodes[parent].children.push(child);
+    self.nodes[child].parent = Some(parent);
+  }
+
+  // take `node` out of its parent's children
+  fn detach(&mut self, node: usize) {
+    if let Some(parent) = self.nodes[node].parent.take() {
+      self.nodes[parent].children.retain(|&child| child != node);
+    }
+  }
+
+  fn tag_name(&self, node: usize) -> &str {
+    match self.nodes[node].node_type {
+      dom::NodeType::Element(ref element) => element.tag_name.as_str(),
+      _ => "",
+    }
+  }
+
+  fn element_data(&self, node: usize) -> &dom::ElementData {
+    match self.nodes[node].node_type {
+      dom::NodeType::Element(ref element) => element,
+      _ => panic!("not an element"),
+    }
+  }
+
+  fn create_element(&mut self, tag: &Tag) -> usize {
+    // (the tokenizer has already dropped duplicate attributes)
+    let mut attributes = HashMap::new();
+    for (name, value) in tag.attributes.iter() {
+      attributes.insert(name.clone(), value.clone());
+    }
+
+    self.add_node(dom::NodeType::Element(dom::ElementData {
+      tag_name: tag.name.clone(),
+      attributes,
+    }))
+  }
+
+  // create an element for `tag`, append it to the current
+  // node and push it onto the stack of open elements
+  fn insert_element(&mut self, tag: &Tag) -> usize {
+    let node = self.create_element(tag);
+    let parent = self.current_node();
+    self.nodes[node].foreign = self.nodes[parent].foreign || tag.name == "svg" || tag.name == "math";
+    self.append_child(parent, node);
+    self.open_elements.push(node);
+    node
+  }
+
+  // insert an element that can't have children, leaving it closed
+  fn insert_void_element(&mut self, tag: &Tag) {
+    self.insert_element(tag);
+    self.open_elements.pop();
+  }
+
+  // insert a raw text or RCDATA element, whose contents the
+  // tokenizer reads as text up to its end tag
+  fn insert_text_element(&mut self, tag: &Tag, state: State) {
+    self.insert_element(tag);
+    self.text_start = self.tokenizer.position();
+    self.tokenizer.switch_to(state);
+    self.original_mode = self.mode;
+    self.mode = InsertionMode::Text;
+  }
+
+  fn insert_comment(&mut self, comment: String, parent: usize) {
+    let node = self.add_node(dom::NodeType::Comment(comment));
+    self.append_child(parent, node);
+  }
+
+  fn insert_comment_here(&mut self, comment: String) {
+    self.insert_comment(comment, self.current_node());
+  }
+
+  // add `c` to the current node's last text child, or start
+  // a new text child if it doesn't end with one
+  fn insert_character(&mut self, c: char) {
+    let parent = self.current_node();
+    if let Some(&last) = self.nodes[parent].children.last() {
+      if let dom::NodeType::Text(ref mut text) = self.nodes[last].node_type {
+        text.push(c);
+        return;
+      }
+    }
+
+    let node = self.add_node(dom::NodeType::Text(c.to_string()));
+    self.append_child(parent, node);
+  }
+
+  // copy attributes from a repeated <html> or <body> start tag
+  // that the element doesn't already have
+  fn add_missing_attributes(&mut self, node: usize, tag: &Tag) {
+    if let dom::NodeType::Element(ref mut element) = self.nodes[node].node_type {
+      for (name, value) in tag.attributes.iter() {
+        element.attributes.entry(name.clone()).or_insert_with(|| value.clone());
+      }
+    }
+  }
+
+  // pop the current node off the stack of open elements
+  fn pop(&mut self) {
+    let node = self.open_elements.pop().unwrap();
+    if self.tag_name(node) == "style" {
+      self.parse_style_element(node);
+    }
+  }
+
+  // pop elements until one named in `names` has been popped
+  fn pop_until(&mut self, names: &[&str]) {
+    while let Some(&node) = self.open_elements.last() {
+      let done = names.contains(&self.tag_name(node));
+      self.pop();
+      if done { break }
+    }
+  }
+
+  // is an element named in `names` open, with no element
+  // that bounds `scope` between it and the current node?
+  fn has_in_scope(&self, names: &[&str], scope: Scope) -> bool {
+    for &node in self.open_elements.iter().rev() {
+      let name = self.tag_name(node);
+      if names.contains(&name) {
+        return true;
+      }
+      let boundary = match scope {
+        Scope::Table => matches!(name, "html" | "table" | "template"),
+        _ => SCOPE_BOUNDARIES.contains(&name)
+          || (scope == Scope::ListItem && matches!(name, "ol" | "ul"))
+          || (scope == Scope::Button && name == "button"),
+      };
+      if boundary {
+        return false;
+      }
+    }
+    false
+  }
+
+  // pop elements whose end tags can be implied, other than `except`
+  fn generate_implied_end_tags(&mut self, except: Option<&str>) {
+    loop {
+      let name = self.tag_name(self.current_node());
+      if !IMPLIED_END_TAGS.contains(&name) || Some(name) == except {
+        break;
+      }
+      self.pop();
+    }
+  }
+
+  fn close_p(&mut self) {
+    self.generate_implied_end_tags(Some("p"));
+    if self.tag_name(self.current_node()) != "p" {
+      self.misnested_end_tag("p");
+    }
+    self.pop_until(&["p"]);
+  }
+
+  // block-level start tags implicitly close an open <p>
+  fn close_p_in_button_scope(&mut self) {
+    if self.has_in_scope(&["p"], Scope::Button) {
+      self.close_p();
+    }
+  }
+
+  // add `node` to the list of active formatting elements,
+  // keeping at most three identical ones after the last marker
+  fn push_active_formatting(&mut self, node: usize) {
+    let mut same = Vec::new();
+    for (index, &entry) in self.active_formatting.iter().enumerate().rev() {
+      match entry {
+        Formatting::Marker => break,
+        Formatting::Element(other) => {
+          let (a, b) = (self.element_data(other), self.element_data(node));
+          if a.tag_name == b.tag_name && a.attributes == b.attributes {
+            same.push(index);
+          }
+        }
+      }
+    }
+    if same.len() >= 3 {
+      self.active_formatting.remove(*same.last().unwrap());
+    }
+
+    self.active_formatting.push(Formatting::Element(node));
+  }
+
+  fn remove_from_active_formatting(&mut self, node: usize) {
+    self.active_formatting.retain(|&entry| entry != Formatting::Element(node));
+  }
+
+  fn clear_active_formatting_to_marker(&mut self) {
+    while let Some(entry) = self.active_formatting.pop() {
+      if entry == Formatting::Marker { break }
+    }
+  }
+
+  // the last active formatting element named `name` after the last marker
+  fn formatting_element(&self, name: &str) -> Option<usize> {
+    for &entry in self.active_formatting.iter().rev() {
+      match entry {
+        Formatting::Marker => return None,
+        Formatting::Element(node) if self.tag_name(node) == name => return Some(node),
+        _ => {}
+      }
+    }
+    None
+  }
+
+  // a copy of `node` (without children) for a formatting
+  // element that's reopened
+  fn clone_element(&mut self, node: usize) -> usize {
+    let element = self.element_data(node).clone();
+    self.add_node(dom::NodeType::Element(element))
+  }
+
+  // reopen formatting elements that were closed implicitly,
+  // e.g. the <b> in `<p><b>one<p>two`
+  fn reconstruct_active_formatting_elements(&mut self) {
+    let is_open = |parser: &Parser, entry: Formatting| match entry {
+      Formatting::Marker => true,
+      Formatting::Element(node) => parser.open_elements.contains(&node),
+    };
+
+    let last = match self.active_formatting.last() {
+      Some(&entry) => entry,
+      None => return,
+    };
+    if is_open(self, last) {
+      return;
+    }
+
+    // rewind to the first entry after the last open one
+    let mut index = self.active_formatting.len() - 1;
+    while index > 0 && !is_open(self, self.active_formatting[index - 1]) {
+      index -= 1;
+    }
+
+    for entry in index..self.active_formatting.len() {
+      if let Formatting::Element(node) = self.active_formatting[entry] {
+        let clone = self.clone_element(node);
+        self.append_child(self.current_node(), clone);
+        self.open_elements.push(clone);
+        self.active_formatting[entry] = Formatting::Element(clone);
+      }
+    }
+  }
+
+  // the adoption agency algorithm, which handles the end tag
+  // of a formatting element, restructuring the tree when it's
+  // misnested, e.g. `<b>1<p>2</b>3</p>`
+  fn adoption_agency(&mut self, subject: &str) {
+    let current = self.current_node();
+    if self.tag_name(current) == subject && !self.active_formatting.contains(&Formatting::Element(current)) {
+      self.pop();
+      return;
+    }
+
+    let mut warned = false;
+    for _ in 0..8 {
+      let formatting_element = match self.formatting_element(subject) {
+        Some(node) => node,
+        None => return self.any_other_end_tag(subject),
+      };
+
+      let fe_index = match self.open_elements.iter().position(|&node| node == formatting_element) {
+        Some(index) => index,
+        None => {
+          self.unexpected_end_tag(subject);
+          self.remove_from_active_formatting(formatting_element);
+          return;
+        }
+      };
+      if !self.has_in_scope(&[subject], Scope::Default) {
+        self.unexpected_end_tag(subject);
+        return;
+      }
+      if formatting_element != self.current_node() && !warned {
+        self.misnested_end_tag(subject);
+        warned = true;
+      }
+
+      // the first special element inside the formatting element
+      let furthest_block = self.open_elements[fe_index + 1..].iter()
+        .position(|&node| SPECIAL.contains(&self.tag_name(node)))
+        .map(|offset| fe_index + 1 + offset);
+      let fb_index = match furthest_block {
+        Some(index) => index,
+        None => {
+          // nothing to restructure: just close it
+          while self.open_elements.len() > fe_index {
+            self.pop();
+          }
+          self.remove_from_active_formatting(formatting_element);
+          return;
+        }
+      };
+      let furthest_block = self.open_elements[fb_index];
+      let common_ancestor = self.open_elements[fe_index - 1];
+      let mut bookmark = self.active_formatting.iter()
+        .position(|&entry| entry == Formatting::Element(formatting_element)).unwrap();
+
+      // walk up from the furthest block to the formatting element,
+      // reopening formatting elements and dropping the others
+      let mut node_index = fb_index;
+      let mut last_node = furthest_block;
+      let mut counter = 0;
+      loop {
+        counter += 1;
+        node_index -= 1;
+        let node = self.open_elements[node_index];
+        if node == formatting_element { break }
+
+        let mut entry = self.active_formatting.iter().position(|&entry| entry == Formatting::Element(node));
+        if counter > 3 {
+          if let Some(index) = entry {
+            self.active_formatting.remove(index);
+            if index < bookmark {
+              bookmark -= 1;
+            }
+            entry = None;
+          }
+        }
+        let entry = match entry {
+          Some(index) => index,
+          None => {
+            self.open_elements.remove(node_index);
+            continue;
+          }
+        };
+
+        let clone = self.clone_element(node);
+        self.active_formatting[entry] = Formatting::Element(clone);
+        self.open_elements[node_index] = clone;
+        if last_node == furthest_block {
+          bookmark = entry + 1;
+        }
+        self.append_child(clone, last_node);
+        last_node = clone;
+      }
+
+      self.append_child(common_ancestor, last_node);
+
+      // move the furthest block's children into a copy of the
+      // formatting element, and put that in the furthest block
+      let clone = self.clone_element(formatting_element);
+      let children = self.nodes[furthest_block].children.clone();
+      for child in children.into_iter() {
+        self.append_child(clone, child);
+      }
+      self.append_child(furthest_block, clone);
+
+      let fe_entry = self.active_formatting.iter()
+        .position(|&entry| entry == Formatting::Element(formatting_element)).unwrap();
+      self.active_formatting.remove(fe_entry);
+      if fe_entry < bookmark {
+        bookmark -= 1;
+      }
+      self.active_formatting.insert(bookmark.min(self.active_formatting.len()), Formatting::Element(clone));
+
+      self.open_elements.retain(|&node| node != formatting_element);
+      let fb_index = self.open_elements.iter().position(|&node| node == furthest_block).unwrap();
+      self.open_elements.insert(fb_index + 1, clone);
+    }
+  }
+
+  // parse the CSS of a <style> element as it's closed, warning
+  // about its errors (the document gets the stylesheet once
+  // the tree is built)
+  fn parse_style_element(&mut self, node: usize) {
+    // report CSS warnings at their position in the HTML source
+    let (sheet, warnings) = css::parse_with_warnings(self.style_text(node));
+    for warning in warnings {
+      let offset = self.text_start + warning.byte_offset;
+      self.tokenizer.warn_at(offset, &warning.expected, &warning.found);
+    }
+    self.parsed_styles.insert(node, sheet);
+  }
+
+  fn style_text(&self, node: usize) -> String {
+    let mut style = String::new();
+    for &child in self.nodes[node].children.iter() {
+      if let dom::NodeType::Text(ref text) = self.nodes[child].node_type {
+        style.push_str(text);
+      }
+    }
+    style
+  }
+
+  fn unexpected_doctype(&mut self) {
+    let start = self.tokenizer.token_start;
+    self.tokenizer.warn_at(start, "the doctype before any other content", "`<!DOCTYPE>`");
+  }
+
+  fn unexpected_start_tag(&mut self, name: &str) {
+    let start = self.tokenizer.token_start;
+    self.tokenizer.warn_at(start, "content allowed here", &format!("`<{}>`", name));
+  }
+
+  // an end tag with no open element to close
+  fn unexpected_end_tag(&mut self, name: &str) {
+    let start = self.tokenizer.token_start;
+    let expected = format!("an open `{}` element", name);
+    self.tokenizer.warn_at(start, &expected, &format!("`</{}>`", name));
+  }
+
+  // an end tag that closes elements other than the current node
+  fn misnested_end_tag(&mut self, name: &str) {
+    let start = self.tokenizer.token_start;
+    let expected = format!("`</{}>`", self.tag_name(self.current_node()));
+    self.tokenizer.warn_at(start, &expected, &format!("`</{}>`", name));
+  }
+
+  // content after the end of the body
+  fn unexpected_token(&mut self, token: &Token) {
+    let (start, found) = self.describe_token(token);
+    self.tokenizer.warn_at(start, "the end of the document", &found);
+  }
+
+  // where `token` starts, and what to call it in a warning
+  fn describe_token(&self, token: &Token) -> (usize, String) {
+    let found = match *token {
+      Token::StartTag(ref tag) => format!("`<{}>`", tag.name),
+      Token::EndTag(ref tag) => format!("`</{}>`", tag.name),
+      Token::Eof => "the end of the input".to_string(),
+      _ => "text".to_string(),
+    };
+    let start = match *token {
+      Token::Character(c) => self.tokenizer.position() - c.len_utf8(),
+      Token::Eof => self.tokenizer.position(),
+      _ => self.tokenizer.token_start,
+    };
+    (start, found)
+  }
+
+  // turn the node at `index` (and its children) into a dom::Node
+  // (also gathering the stylesheets of <style> elements in
+  // tree order, parsing any that were never closed)
+  fn build(&mut self, index: usize) -> dom::Node {
+    if self.tag_name(index) == "style" {
+      let sheet = match self.parsed_styles.remove(&index) {
+        Some(sheet) => sheet,
+        None => css::parse(self.style_text(index)),
+      };
+      self.style_sheets.push(sheet);
+    }
+
+    let node_type = std::mem::replace(&mut self.nodes[index].node_type, dom::NodeType::Text(String::new()));
+    let children = std::mem::take(&mut self.nodes[index].children);
+    dom::Node {
+      children: children.into_iter().map(|child| self.build(child)).collect(),
+      node_type,
+    }
+  }
+}
+
+// a start tag for an element the parser inserts itself
+fn implied_tag(name: &str) -> Tag {
+  Tag { name: name.to_string(), ..Tag::default() }
+}
+
+// the document mode a doctype asks for (the WHATWG quirks
+// mode rules, minus the iframe srcdoc exception)
+fn document_mode(doctype: &Doctype) -> dom::DocumentMode {
+  let public_id = doctype.public_id.as_ref().map(|id| id.to_ascii_lowercase());
+  let system_id = doctype.system_id.as_ref().map(|id| id.to_ascii_lowercase());
+  let public_starts_with = |prefixes: &[&str]| {
+    public_id.as_ref().is_some_and(|id| prefixes.iter().any(|prefix| id.starts_with(prefix)))
+  };
+
+  let quirks = doctype.force_quirks
+    || doctype.name.as_deref() != Some("html")
+    || matches!(public_id.as_deref(),
+                Some("-//w3o//dtd w3 html strict 3.0//en//" | "-/w3c/dtd html 4.0 transitional/en" | "html"))
+    || system_id.as_deref() == Some("http://www.ibm.com/data/dtd/v11/ibmxhtml1-transitional.dtd")
+    || public_starts_with(QUIRKS_PUBLIC_PREFIXES)
+    || (system_id.is_none() && public_starts_with(HTML4_TRANSITIONAL_PREFIXES));
+
+  if quirks {
+    dom::DocumentMode::Quirks
+  } else if public_starts_with(LIMITED_QUIRKS_PUBLIC_PREFIXES)
+    || (system_id.is_some() && public_starts_with(HTML4_TRANSITIONAL_PREFIXES)) {
+    dom::DocumentMode::LimitedQuirks
+  } else {
+    dom::DocumentMode::NoQuirks
+  }
+}
+
+// parse HTML source and return a root document node, which
+// always has an <html> element with a <head> and a <body>
+pub fn parse(source: String) -> dom::Node {
+  parse_with_warnings(source).0
+}
+
+// parse HTML source, returning the root document node along
+// with a warning for each parse error recovered from
+pub fn parse_with_warnings(source: String) -> (dom::Node, Vec<ParseError>) {
+  let mut parser = Parser {
+    tokenizer: Tokenizer::new(source),
+    nodes: Vec::new(),
+    mode: InsertionMode::Initial,
+    original_mode: InsertionMode::Initial,
+    open_elements: Vec::new(),
+    active_formatting: Vec::new(),
+    head: None,
+    skip_newline: false,
+    text_start: 0,
+    document_mode: dom::DocumentMode::NoQuirks,
+    parsed_styles: HashMap::new(),
+    style_sheets: Vec::new(),
+  };
+  parser.add_node(dom::NodeType::Document(dom::DocumentData::new(dom::DocumentMode::NoQuirks)));
+  parser.run();
+
+  let mut root = parser.build(DOCUMENT);
+  if let dom::NodeType::Document(ref mut document) = root.node_type {
+    document.set_mode(parser.document_mode);
+  }
+  root.update_stylesheets_from(std::mem::take(&mut parser.style_sheets));
+
+  let mut warnings = std::mem::take(&mut parser.tokenizer.warnings);
+  warnings.sort_by_key(|warning| warning.byte_offset);
+  (root, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{parse, parse_with_warnings};
+  use crate::dom::{DocumentMode, NodeType};
+
+  // the dump of the <body> element's children, one level less indented
+  fn body(source: &str) -> String {
+    let root = parse(source.to_string());
+    let body = &root.children[0].children[1];
+    let mut out = String::new();
+    for child in body.children.iter() {
+      out.push_str(&child.dump());
+    }
+    out
+  }
+
+  #[test]
+  fn parses_nested_elements_and_text() {
+    let root = parse("<div id=\"main\" class='a'><p>Hello <em>world</em>!</p></div>".to_string());
+    assert_eq!(root.dump().as_str(), "#document
+  <html>
+    <head>
+    <body>
+      <div class=\"a\" id=\"main\">
+        <p>
+          \"Hello \"
+          <em>
+            \"world\"
+          \"!\"
+");
+  }
+
+  #[test]
+  fn parses_comments() {
+    let root = parse("<!-- before --><div><!-- a comment --></div>".to_string());
+    match root.children[0].node_type {
+      NodeType::Comment(ref comment) => assert_eq!(comment.as_str(), " before "),
+      _ => panic!("expected a comment"),
+    }
+    match root.children[1].children[1].children[0].children[0].node_type {
+      NodeType::Comment(ref comment) => assert_eq!(comment.as_str(), " a comment "),
+      _ => panic!("expected a comment"),
+    }
+  }
+
+  #[test]
+  fn style_elements_become_stylesheets() {
+    let root = parse("<style>p { color: red; } a < b {}</style><link rel=stylesheet href=x.css><p>text</p>".to_string());
+    match root.node_type {
+      NodeType::Document(ref document) => {
+        assert_eq!(document.stylesheets().len(), 2);
+        assert_eq!(document.stylesheets()[0].inline().unwrap().rules.len(), 1);
+        assert!(document.stylesheets()[1].inline().is_none());
+      }
+      _ => panic!("expected a document"),
+    }
+
+    // the <style> element stays in the tree
+    assert_eq!(root.children[0].children[0].dump().as_str(), "<head>
+  <style>
+    \"p { color: red; } a < b {}\"
+  <link href=\"x.css\" rel=\"stylesheet\">
+");
+
+    // several sheets keep their tree order, and one left open
+    // at the end of the input still counts
+    let root = parse("<style>a {}</style><p><style>b {} c {}</style><style>d {} e {} f {}".to_string());
+    match root.node_type {
+      NodeType::Document(ref document) => {
+        let rules: Vec<usize> = document.stylesheets().iter()
+          .map(|sheet| sheet.inline().unwrap().rules.len()).collect();
+        assert_eq!(rules, vec![1, 2, 3]);
+      }
+      _ => panic!("expected a document"),
+    }
+  }
+
+  #[test]
+  fn keeps_whitespace_and_unquoted_attributes() {
+    assert_eq!(body("<ul>\n  <li class=a>one</li>\n</ul>"), "<ul>
+  \"\\n  \"
+  <li class=\"a\">
+    \"one\"
+  \"\\n\"
+");
+  }
+
+  #[test]
+  fn inserts_html_head_and_body() {
+    let root = parse("\n<html lang=en>\n<title>x</title>\n<p>y\n</html>\n".to_string());
+    assert_eq!(root.dump().as_str(), "#document
+  <html lang=\"en\">
+    <head>
+      <title>
+        \"x\"
+      \"\\n\"
+    <body>
+      <p>
+        \"y\\n\\n\"
+");
+
+    let root = parse("<link rel=stylesheet><body class=b></body><!--end-->".to_string());
+    assert_eq!(root.dump().as_str(), "#document
+  <html>
+    <head>
+      <link rel=\"stylesheet\">
+    <body class=\"b\">
+    <!--end-->
+");
+  }
+
+  #[test]
+  fn implies_end_tags() {
+    assert_eq!(body("<p>one<p>two<div>three</div>"), "<p>
+  \"one\"
+<p>
+  \"two\"
+<div>
+  \"three\"
+");
+    assert_eq!(body("<ul><li>a<li>b<ul><li>c</ul></ul><dl><dt>d<dd>e<dt>f</dl>"), "<ul>
+  <li>
+    \"a\"
+  <li>
+    \"b\"
+    <ul>
+      <li>
+        \"c\"
+<dl>
+  <dt>
+    \"d\"
+  <dd>
+    \"e\"
+  <dt>
+    \"f\"
+");
+    assert_eq!(body("<select><option>a<option>b</select>"), "<select>
+  <option>
+    \"a\"
+  <option>
+    \"b\"
+");
+  }
+
+  #[test]
+  fn implies_table_rows_and_cells() {
+    assert_eq!(body("<table><tr><td>a<td>b<tr><th>c</table>"), "<table>
+  <tbody>
+    <tr>
+      <td>
+        \"a\"
+      <td>
+        \"b\"
+    <tr>
+      <th>
+        \"c\"
+");
+  }
+
+  #[test]
+  fn adoption_agency_fixes_misnested_formatting() {
+    assert_eq!(body("<b><i>x</b>y</i>"), "<b>
+  <i>
+    \"x\"
+<i>
+  \"y\"
+");
+    assert_eq!(body("<b>1<p>2</b>3</p>"), "<b>
+  \"1\"
+<p>
+  <b>
+    \"2\"
+  \"3\"
+");
+    assert_eq!(body("<p><b class=x>one<p>two"), "<p>
+  <b class=\"x\">
+    \"one\"
+<p>
+  <b class=\"x\">
+    \"two\"
+");
+    assert_eq!(body("<a href=1>x<a href=2>y"), "<a href=\"1\">
+  \"x\"
+<a href=\"2\">
+  \"y\"
+");
+  }
+
+  #[test]
+  fn unquoted_boolean_and_duplicate_attributes() {
+    assert_eq!(body("<input disabled class=a CLASS=b><table><td colspan=2 data-x-y=\"z\">"), "<input class=\"a\" disabled=\"\">
+<table>
+  <tbody>
+    <tr>
+      <td colspan=\"2\" data-x-y=\"z\">
+");
+  }
+
+  #[test]
+  fn void_elements_have_no_children() {
+    assert_eq!(body("<p>a<br>b<img src=x.png>c</br>d<hr>e<input type=text>"), "<p>
+  \"a\"
+  <br>
+  \"b\"
+  <img src=\"x.png\">
+  \"c\"
+  <br>
+  \"d\"
+<hr>
+\"e\"
+<input type=\"text\">
+");
+    assert_eq!(body("<table><col><tr><td>x</table>"), "<table>
+  <col>
+  <tbody>
+    <tr>
+      <td>
+        \"x\"
+");
+  }
+
+  #[test]
+  fn self_closing_only_applies_to_void_and_foreign_elements() {
+    let (root, warnings) = parse_with_warnings("<!DOCTYPE html><br/><div/>x<svg><circle r=\"1\"/><g/></svg>".to_string());
+    assert_eq!(root.children[1].children[1].dump().as_str(), "<body>
+  <br>
+  <div>
+    \"x\"
+    <svg>
+      <circle r=\"1\">
+      <g>
+");
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].column, 21);
+    assert_eq!(warnings[0].found.as_str(), "`/>`");
+  }
+
+  #[test]
+  fn recovers_from_unmatched_tags() {
+    let (root, warnings) = parse_with_warnings("<!DOCTYPE html><div>\n  <span>text</div></span>after".to_string());
+    assert_eq!(root.children[1].children[1].dump().as_str(), "<body>
+  <div>
+    \"\\n  \"
+    <span>
+      \"text\"
+  \"after\"
+");
+
+    assert_eq!(warnings.len(), 2);
+    assert_eq!((warnings[0].line, warnings[0].column), (2, 13));
+    assert_eq!(warnings[0].expected.as_str(), "`</span>`");
+    assert_eq!(warnings[0].found.as_str(), "`</div>`");
+    assert_eq!(warnings[1].expected.as_str(), "an open `span` element");
+
+    // css warnings are reported where they are in the html
+    let (_, warnings) = parse_with_warnings("<!DOCTYPE html><p></p>\n<style>\np { color }</style>".to_string());
+    assert_eq!((warnings[0].line, warnings[0].column), (3, 11));
+    assert_eq!(warnings[0].expected.as_str(), "`:`");
+  }
+
+  #[test]
+  fn doctypes_decide_the_document_mode() {
+    let mode = |source: &str| match parse(source.to_string()).node_type {
+      NodeType::Document(ref document) => document.mode(),
+      _ => panic!("expected a document"),
+    };
+
+    assert_eq!(mode("<!DOCTYPE html>"), DocumentMode::NoQuirks);
+    assert_eq!(mode("<!doctype HTML SYSTEM \"about:legacy-compat\">"), DocumentMode::NoQuirks);
+    assert_eq!(mode("<p>"), DocumentMode::Quirks);
+    assert_eq!(mode("<!DOCTYPE svg>"), DocumentMode::Quirks);
+    assert_eq!(mode("<!DOCTYPE html PUBLIC \"-//W3C//DTD HTML 3.2 Final//EN\">"), DocumentMode::Quirks);
+    assert_eq!(mode("<!DOCTYPE html PUBLIC \"-//W3C//DTD HTML 4.01 Transitional//EN\">"), DocumentMode::Quirks);
+    assert_eq!(mode("<!DOCTYPE html PUBLIC \"-//W3C//DTD HTML 4.01 Transitional//EN\"
+                     \"http://www.w3.org/TR/html4/loose.dtd\">"), DocumentMode::LimitedQuirks);
+    assert_eq!(mode("<!DOCTYPE html PUBLIC \"-//w3c//dtd xhtml 1.0 strict//en\">"), DocumentMode::NoQuirks);
+
+    let (root, warnings) = parse_with_warnings("<!-- a -->\n<!DOCTYPE html PUBLIC \"-//W3C//DTD HTML 4.01//EN\" \"\"><p>".to_string());
+    assert_eq!(root.children[1].node_type.to_string(), "<!DOCTYPE html \"-//W3C//DTD HTML 4.01//EN\" \"\">");
+    assert_eq!((warnings[0].line, warnings[0].column), (2, 1));
+    assert_eq!(warnings[0].found.as_str(), "a legacy doctype");
+
+    // a missing doctype is a parse error too, and tables may then
+    // sit inside paragraphs
+    let (root, warnings) = parse_with_warnings("<p><table></table>".to_string());
+    assert_eq!(warnings[0].expected.as_str(), "`<!DOCTYPE html>`");
+    assert_eq!(root.children[0].children[1].children[0].children.len(), 1);
+    let root = parse("<!DOCTYPE html><p><table></table>".to_string());
+    assert_eq!(root.children[1].children[1].children.len(), 2);
+  }
+
+  #[test]
+  fn raw_text_elements_keep_their_contents_as_text() {
+    let root = parse("<title>a &amp; <b>b</b></title><script>if (a < b) { x = \"</p>\"; }</script>".to_string());
+    assert_eq!(root.children[0].children[0].dump().as_str(), "<head>
+  <title>
+    \"a & <b>b</b>\"
+  <script>
+    \"if (a < b) { x = \\\"</p>\\\"; }\"
+");
+
+    assert_eq!(body("<p><xmp><i>&amp;</xmp><textarea>\n<i>&amp;</i></textarea><styles>"), "<p>
+<xmp>
+  \"<i>&amp;\"
+<textarea>
+  \"<i>&</i>\"
+<styles>
+");
+    assert_eq!(body("<iframe><p></iframe><noembed></p></noembed><noframes></noframes>"), "<iframe>
+  \"<p>\"
+<noembed>
+  \"</p>\"
+<noframes>
+");
+  }
+}
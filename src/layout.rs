@@ -0,0 +1,897 @@
+//! a layout tree module
+//! px sizes for now
+
+use crate::style;
+use crate::dom;
+use crate::font;
+use crate::computed::Length;
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Dimensions {
+  pub content: Rect,
+  pub padding: EdgeSizes,
+  pub border: EdgeSizes,
+  pub margin: EdgeSizes,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Rect {
+  pub x: f32,
+  pub y: f32,
+  pub width: f32,
+  pub height: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct EdgeSizes {
+  pub left: f32,
+  pub right: f32,
+  pub top: f32,
+  pub bottom : f32,
+}
+
+#[derive(Debug)]
+pub struct LayoutBox<'a> {
+  pub dimensions: Dimensions,
+  pub box_type: BoxType<'a>,
+  pub children: Vec<LayoutBox<'a>>,
+  // line boxes (only for boxes establishing an inline formatting context)
+  pub lines: Vec<LineBox<'a>>,
+  // the piece of an inline box on each line it spans, in order; the
+  // first only has left edges and the last only right ones, and
+  // `dimensions` covers them all
+  pub fragments: Vec<Dimensions>,
+}
+
+// a single line of inline content
+#[derive(Debug)]
+pub struct LineBox<'a> {
+  pub rect: Rect,
+  // y position of the line's baseline
+  pub baseline: f32,
+  pub fragments: Vec<TextFragment<'a>>,
+}
+
+// a run of text placed on a line, styled by `style`
+// (the nearest element box it belongs to)
+#[derive(Debug)]
+pub struct TextFragment<'a> {
+  pub rect: Rect,
+  pub text: String,
+  pub font_size: f32,
+  pub style: &'a style::StyledNode<'a>,
+}
+
+// a word waiting to be placed on a line
+struct InlineWord<'a> {
+  text: String,
+  style: &'a style::StyledNode<'a>,
+  // was the word preceded by (collapsed) whitespace?
+  space_before: bool,
+  // the inline boxes the word is inside, outermost first
+  boxes: Vec<usize>,
+  // how many of the innermost `boxes` start and end at this word
+  opens: usize,
+  closes: usize,
+}
+
+// gathers the words of an inline formatting context, numbering
+// the inline boxes they're in in tree order
+struct WordCollector<'a> {
+  words: Vec<InlineWord<'a>>,
+  boxes: Vec<&'a style::StyledNode<'a>>,
+  // the boxes we're inside, outermost first
+  path: Vec<usize>,
+  // boxes entered since the last word
+  pending_opens: usize,
+  pending_space: bool,
+}
+
+// the part of an inline box on one line, with x positions
+// relative to the start of the line
+struct InlineExtent {
+  line: usize,
+  start: f32,
+  end: f32,
+  first: bool,
+  last: bool,
+}
+
+#[derive(Debug)]
+pub enum BoxType<'a> {
+  BlockNode(&'a style::StyledNode<'a>),
+  InlineNode(&'a style::StyledNode<'a>),
+  AnonymousBlock,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Display {
+  Inline,
+  Block,
+  None,
+}
+
+impl Rect {
+  // return a copy of this rect grown by `edge` on every side
+  pub fn expanded_by(&self, edge: &EdgeSizes) -> Rect {
+    Rect {
+      x: self.x - edge.left,
+      y: self.y - edge.top,
+      width: self.width + edge.left + edge.right,
+      height: self.height + edge.top + edge.bottom,
+    }
+  }
+}
+
+impl Dimensions {
+  // area covered by the content area plus its padding
+  pub fn padding_box(&self) -> Rect {
+    self.content.expanded_by(&self.padding)
+  }
+
+  // area covered by the content area plus padding and borders
+  pub fn border_box(&self) -> Rect {
+    self.padding_box().expanded_by(&self.border)
+  }
+
+  // area covered by the content area plus padding, borders and margin
+  pub fn margin_box(&self) -> Rect {
+    self.border_box().expanded_by(&self.margin)
+  }
+}
+
+// build a layout tree for `style_node` and lay it out inside
+// `containing_block` (usually the viewport)
+pub fn layout_tree<'a>(style_node: &'a style::StyledNode<'a>, mut containing_block: Dimensions) -> LayoutBox<'a> {
+  // the layout algorithm expects the container height to start at 0
+  containing_block.content.height = 0.0;
+
+  let mut root = build_layout_tree(style_node);
+  root.layout(containing_block, None);
+  root
+}
+
+// build and return a layout tree
+pub fn build_layout_tree<'a>(style_node: &'a style::StyledNode<'a>) -> LayoutBox<'a> {
+  // root element
+  let mut root = LayoutBox::new(match style_node.display() {
+    Display::Block => BoxType::BlockNode(style_node),
+    Display::Inline => BoxType::InlineNode(style_node),
+    Display::None => panic!("root node has display: none"),
+  });
+
+  for child in style_node.children.iter() {
+    match child.display() {
+      Display::Block => root.children.push(build_layout_tree(child)),
+      Display::Inline => root.get_inline_container().children.push(build_layout_tree(child)),
+      Display::None => {},
+    }
+  }
+
+  root
+}
+
+impl<'a> LayoutBox<'a> {
+  fn new(box_type: BoxType) -> LayoutBox {
+    LayoutBox {
+      box_type,
+      dimensions: Default::default(),
+      children: Vec::new(),
+      lines: Vec::new(),
+      fragments: Vec::new(),
+    }
+  }
+
+  // get the style node for this box
+  // (anonymous boxes don't have one)
+  fn get_style_node(&self) -> Option<&'a style::StyledNode<'a>> {
+    match self.box_type {
+      BoxType::BlockNode(node) | BoxType::InlineNode(node) => Some(node),
+      BoxType::AnonymousBlock => None,
+    }
+  }
+
+  fn get_inline_container(&mut self) -> &mut LayoutBox<'a> {
+    match self.box_type {
+      BoxType::InlineNode(_) | BoxType::AnonymousBlock => self,
+      BoxType::BlockNode(_) => {
+        match self.children.last() {
+          Some(&LayoutBox { box_type: BoxType::AnonymousBlock,..}) => {}
+          _ => self.children.push(LayoutBox::new(BoxType::AnonymousBlock))
+        }
+        self.children.last_mut().unwrap()
+      }
+    }
+  }
+
+  // lay out this box and its descendants
+  // (`parent_style` is the style of the parent box, which
+  // text directly inside an anonymous block is drawn with)
+  fn layout(&mut self, containing_block: Dimensions, parent_style: Option<&'a style::StyledNode<'a>>) {
+    match self.box_type {
+      BoxType::BlockNode(_) | BoxType::AnonymousBlock => self.layout_block(containing_block, parent_style),
+      // inline boxes are placed by the line layout of the
+      // anonymous block they're in
+      BoxType::InlineNode(_) => {}
+    }
+  }
+
+  // lay out a block-level box and its descendants
+  fn layout_block(&mut self, containing_block: Dimensions, parent_style: Option<&'a style::StyledNode<'a>>) {
+    // width depends on the parent, so it has to come first
+    self.calculate_block_width(&containing_block);
+
+    // position the box below any previous boxes in the container
+    self.calculate_block_position(&containing_block);
+
+    // children are stacked inside this box's content area,
+    // or flowed into lines if they're inline
+    match self.box_type {
+      BoxType::AnonymousBlock => self.layout_inline_children(parent_style),
+      _ => self.layout_block_children(),
+    }
+
+    // height depends on the children, so it has to come last
+    self.calculate_block_height();
+  }
+
+  // calculate width, horizontal padding, borders and margins
+  // (see CSS 2.1 section 10.3.3)
+  fn calculate_block_width(&mut self, containing_block: &Dimensions) {
+    let cb_width = containing_block.content.width;
+
+    let style = match self.get_style_node() {
+      Some(style) => style,
+      None => {
+        // anonymous blocks fill their container
+        self.dimensions.content.width = cb_width;
+        return;
+      }
+    };
+
+    let style = &style.computed;
+    let auto = Length::Auto;
+    let zero = Length::Px(0.0);
+
+    let mut width = style.width;
+
+    let mut margin_left = style.margin.left;
+    let mut margin_right = style.margin.right;
+
+    let border_left = Length::Px(style.border_width.left);
+    let border_right = Length::Px(style.border_width.right);
+
+    let padding_left = style.padding.left;
+    let padding_right = style.padding.right;
+
+    let total = [&margin_left, &margin_right, &border_left, &border_right,
+                 &padding_left, &padding_right, &width].iter()
+                 .map(|v| v.to_px(cb_width)).fold(0.0, |a, b| a + b);
+
+    // if width isn't auto and the box is too wide,
+    // auto margins are treated as 0
+    if width != auto && total > cb_width {
+      if margin_left == auto {
+        margin_left = zero;
+      }
+      if margin_right == auto {
+        margin_right = zero;
+      }
+    }
+
+    // the space left over in the containing block (may be negative)
+    let underflow = cb_width - total;
+
+    match (width == auto, margin_left == auto, margin_right == auto) {
+      // over-constrained: adjust the right margin
+      (false, false, false) => {
+        margin_right = px(margin_right.to_px(cb_width) + underflow);
+      }
+
+      // exactly one margin is auto: it takes up the underflow
+      (false, false, true) => { margin_right = px(underflow); }
+      (false, true, false) => { margin_left = px(underflow); }
+
+      // auto width: auto margins become 0
+      (true, _, _) => {
+        if margin_left == auto { margin_left = zero; }
+        if margin_right == auto { margin_right = zero; }
+
+        if underflow >= 0.0 {
+          // expand width to fill the underflow
+          width = px(underflow);
+        } else {
+          // width can't be negative, so shrink the right margin instead
+          width = zero;
+          margin_right = px(margin_right.to_px(cb_width) + underflow);
+        }
+      }
+
+      // both margins auto: center the box
+      (false, true, true) => {
+        margin_left = px(underflow / 2.0);
+        margin_right = px(underflow / 2.0);
+      }
+    }
+
+    let d = &mut self.dimensions;
+    d.content.width = width.to_px(cb_width);
+
+    d.padding.left = padding_left.to_px(cb_width);
+    d.padding.right = padding_right.to_px(cb_width);
+
+    d.border.left = border_left.to_px(cb_width);
+    d.border.right = border_right.to_px(cb_width);
+
+    d.margin.left = margin_left.to_px(cb_width);
+    d.margin.right = margin_right.to_px(cb_width);
+  }
+
+  // calculate vertical padding, borders and margins and place the
+  // box's content area below any previous siblings
+  fn calculate_block_position(&mut self, containing_block: &Dimensions) {
+    let cb_width = containing_block.content.width;
+    let d = &mut self.dimensions;
+
+    match self.box_type {
+      BoxType::BlockNode(style) | BoxType::InlineNode(style) => {
+        let style = &style.computed;
+
+        // vertical percentages are relative to the containing block's width
+        d.margin.top = style.margin.top.to_px(cb_width);
+        d.margin.bottom = style.margin.bottom.to_px(cb_width);
+
+        d.border.top = style.border_width.top;
+        d.border.bottom = style.border_width.bottom;
+
+        d.padding.top = style.padding.top.to_px(cb_width);
+        d.padding.bottom = style.padding.bottom.to_px(cb_width);
+      }
+      BoxType::AnonymousBlock => {}
+    }
+
+    d.content.x = containing_block.content.x +
+                  d.margin.left + d.border.left + d.padding.left;
+
+    // containing_block.content.height is the height of everything
+    // laid out in the container so far
+    d.content.y = containing_block.content.height + containing_block.content.y +
+                  d.margin.top + d.border.top + d.padding.top;
+  }
+
+  // lay out children, stacking them vertically in the content area
+  fn layout_block_children(&mut self) {
+    let style = self.get_style_node();
+    let d = &mut self.dimensions;
+    for child in self.children.iter_mut() {
+      child.layout(d.clone(), style);
+      // track the height so each child is laid out below the previous one
+      d.content.height += child.dimensions.margin_box().height;
+    }
+  }
+
+  // flow inline children into line boxes that fit the content width,
+  // then give each inline box a fragment on every line it spans
+  fn layout_inline_children(&mut self, parent_style: Option<&'a style::StyledNode<'a>>) {
+    let mut collector = WordCollector {
+      words: Vec::new(),
+      boxes: Vec::new(),
+      path: Vec::new(),
+      pending_opens: 0,
+      pending_space: false,
+    };
+    for child in self.children.iter() {
+      collector.collect(child, parent_style);
+    }
+
+    // the horizontal edges of each inline box, which take up
+    // space on the line where it starts or ends
+    let cb_width = self.dimensions.content.width;
+    let edges: Vec<(EdgeSizes, EdgeSizes, EdgeSizes)> = collector.boxes.iter()
+      .map(|style| inline_edges(style, cb_width)).collect();
+    let left = |b: usize| edges[b].0.left + edges[b].1.left + edges[b].2.left;
+    let right = |b: usize| edges[b].0.right + edges[b].1.right + edges[b].2.right;
+
+    let max_width = self.dimensions.content.width;
+    let mut lines = Vec::new();
+    let mut fragments = Vec::new();
+    let mut extents: Vec<Vec<InlineExtent>> = collector.boxes.iter().map(|_| Vec::new()).collect();
+    let mut x = 0.0;
+
+    for word in collector.words.into_iter() {
+      let size = font_size(word.style);
+      let width = text_width(word.text.as_str(), size);
+      let mut space = if word.space_before { text_width(" ", size) } else { 0.0 };
+      let opening = word.boxes.len() - word.opens;
+      let closing = word.boxes.len() - word.closes;
+      let open_width: f32 = word.boxes[opening..].iter().map(|&b| left(b)).sum();
+      let close_width: f32 = word.boxes[closing..].iter().map(|&b| right(b)).sum();
+
+      // wrap at whitespace when the word doesn't fit
+      // (a word wider than the whole line gets a line to itself)
+      if !fragments.is_empty() && x + space + open_width + width + close_width > max_width {
+        lines.push(fragments);
+        fragments = Vec::new();
+        x = 0.0;
+        space = 0.0;
+      }
+
+      // start (or continue, on a new line) the boxes around the word
+      let mut cursor = x + space;
+      for (depth, &b) in word.boxes.iter().enumerate() {
+        if depth >= opening {
+          cursor += left(b);
+        }
+        if depth >= opening || extents[b].last().is_none_or(|extent| extent.line != lines.len()) {
+          extents[b].push(InlineExtent { line: lines.len(), start: cursor, end: cursor,
+                                         first: depth >= opening, last: false });
+        }
+      }
+
+      fragments.push(TextFragment {
+        rect: Rect { x: cursor, y: 0.0, width, height: 0.0 },
+        text: word.text,
+        font_size: size,
+        style: word.style,
+      });
+      cursor += width;
+
+      // extend the boxes over the word, closing the innermost ones
+      for (depth, &b) in word.boxes.iter().enumerate().rev() {
+        let extent = extents[b].last_mut().unwrap();
+        extent.end = cursor;
+        if depth >= closing {
+          extent.last = true;
+          cursor += right(b);
+        }
+      }
+      x = cursor;
+    }
+    if !fragments.is_empty() {
+      lines.push(fragments);
+    }
+
+    // stack the lines, aligning fragments on a common baseline
+    let d = &mut self.dimensions;
+    for fragments in lines.into_iter() {
+      let line = build_line(fragments, d.content.x, d.content.y + d.content.height, d.content.width);
+      d.content.height += line.rect.height;
+      self.lines.push(line);
+    }
+
+    // each inline box's content area is its font's ascent and
+    // descent around the baseline, on every line it spans
+    let content_x = self.dimensions.content.x;
+    let mut box_fragments: Vec<Vec<Dimensions>> = extents.iter().enumerate().map(|(b, extents)| {
+      let (ascent, descent, _) = font_metrics(font_size(collector.boxes[b]));
+      let (ref padding, ref border, ref margin) = edges[b];
+      extents.iter().map(|extent| {
+        let baseline = self.lines[extent.line].baseline;
+        let slice = |edge: &EdgeSizes| EdgeSizes {
+          left: if extent.first { edge.left } else { 0.0 },
+          right: if extent.last { edge.right } else { 0.0 },
+          top: edge.top,
+          bottom: edge.bottom,
+        };
+        Dimensions {
+          content: Rect { x: content_x + extent.start, y: baseline - ascent,
+                          width: extent.end - extent.start, height: ascent + descent },
+          padding: slice(padding),
+          border: slice(border),
+          margin: slice(margin),
+        }
+      }).collect()
+    }).collect();
+
+    let mut next = 0;
+    for child in self.children.iter_mut() {
+      child.assign_inline_fragments(&mut box_fragments, &mut next);
+    }
+  }
+
+  // hand out the fragments from `layout_inline_children`, visiting
+  // boxes in the same order `WordCollector` numbered them
+  fn assign_inline_fragments(&mut self, box_fragments: &mut [Vec<Dimensions>], next: &mut usize) {
+    if !is_inline_element_box(self) {
+      return;
+    }
+    self.fragments = std::mem::take(&mut box_fragments[*next]);
+    *next += 1;
+
+    if let (Some(first), Some(last)) = (self.fragments.first(), self.fragments.last()) {
+      let mut d = first.clone();
+      for fragment in self.fragments.iter() {
+        let (x, y) = (d.content.x.min(fragment.content.x), d.content.y.min(fragment.content.y));
+        let right = (d.content.x + d.content.width).max(fragment.content.x + fragment.content.width);
+        let bottom = (d.content.y + d.content.height).max(fragment.content.y + fragment.content.height);
+        d.content = Rect { x, y, width: right - x, height: bottom - y };
+      }
+      d.padding.right = last.padding.right;
+      d.border.right = last.border.right;
+      d.margin.right = last.margin.right;
+      self.dimensions = d;
+    }
+
+    for child in self.children.iter_mut() {
+      child.assign_inline_fragments(box_fragments, next);
+    }
+  }
+
+  // an explicit `height` overrides the height of the children
+  fn calculate_block_height(&mut self) {
+    // percentage heights against an auto-height container are auto
+    if let Some(Length::Px(h)) = self.get_style_node().map(|style| style.computed.height) {
+      self.dimensions.content.height = h.max(0.0);
+    }
+  }
+
+  // print out the tree
+  pub fn pretty_print(&self, indent_level: usize) {
+    let mut out = String::new();
+    self.dump_into(&mut out, indent_level);
+    print!("{}", out);
+  }
+
+  // dump the layout tree as text: one box per line with its content
+  // rect and any non-zero edges, followed by its line boxes
+  pub fn dump(&self) -> String {
+    let mut out = String::new();
+    self.dump_into(&mut out, 0);
+    out
+  }
+
+  fn dump_into(&self, out: &mut String, indent_level: usize) {
+    let mut spaces = String::new();
+    let mut counter = 0;
+
+    loop {
+      if counter == indent_level { break }
+      counter += 1;
+      spaces.push_str("  ");
+    }
+
+    let d = &self.dimensions;
+    let label = match self.box_type {
+      BoxType::BlockNode(node) => format!("block {}", node.node.node_type),
+      BoxType::InlineNode(node) => format!("inline {}", node.node.node_type),
+      BoxType::AnonymousBlock => "anonymous".to_string(),
+    };
+    out.push_str(&format!("{}{} {}", spaces, label, show_rect(&d.content)));
+    for &(name, edge) in [("padding", &d.padding), ("border", &d.border), ("margin", &d.margin)].iter() {
+      if edge.top != 0.0 || edge.right != 0.0 || edge.bottom != 0.0 || edge.left != 0.0 {
+        out.push_str(&format!(" {} ({}, {}, {}, {})", name,
+                             edge.top, edge.right, edge.bottom, edge.left));
+      }
+    }
+    out.push('\n');
+
+    if self.fragments.len() > 1 {
+      for fragment in self.fragments.iter() {
+        out.push_str(&format!("{}  fragment {}\n", spaces, show_rect(&fragment.content)));
+      }
+    }
+
+    for line in self.lines.iter() {
+      out.push_str(&format!("{}  line {} baseline {}\n", spaces,
+                           show_rect(&line.rect), line.baseline));
+      for fragment in line.fragments.iter() {
+        out.push_str(&format!("{}    \"{}\" {} {}px\n", spaces, fragment.text.escape_default(),
+                             show_rect(&fragment.rect), fragment.font_size));
+      }
+    }
+
+    for child in self.children.iter() {
+      child.dump_into(out, indent_level + 1);
+    }
+  }
+}
+
+// a rect as "(x, y, width, height)"
+fn show_rect(rect: &Rect) -> String {
+  format!("({}, {}, {}, {})", rect.x, rect.y, rect.width, rect.height)
+}
+
+// does `layout_box` belong to an element (or anything else
+// that isn't text) in an inline formatting context?
+fn is_inline_element_box(layout_box: &LayoutBox) -> bool {
+  layout_box.get_style_node().is_some_and(|node| !matches!(node.node.node_type, dom::NodeType::Text(_)))
+}
+
+impl<'a> WordCollector<'a> {
+  // gather the words of an inline subtree in document order,
+  // collapsing runs of whitespace (including across elements)
+  fn collect(&mut self, layout_box: &LayoutBox<'a>, parent_style: Option<&'a style::StyledNode<'a>>) {
+    let node = match layout_box.get_style_node() {
+      Some(node) => node,
+      None => return,
+    };
+
+    match node.node.node_type {
+      dom::NodeType::Text(ref text) => {
+        let style = match parent_style {
+          Some(style) => style,
+          None => node,
+        };
+        let mut word = String::new();
+        for c in text.as_str().chars() {
+          if c.is_whitespace() {
+            if !word.is_empty() {
+              self.push_word(word, style);
+              word = String::new();
+            }
+            self.pending_space = true;
+          } else {
+            word.push(c);
+          }
+        }
+        if !word.is_empty() {
+          self.push_word(word, style);
+        }
+      }
+      _ => {
+        self.path.push(self.boxes.len());
+        self.boxes.push(node);
+        self.pending_opens += 1;
+        let words_before = self.words.len();
+
+        for child in layout_box.children.iter() {
+          self.collect(child, Some(node));
+        }
+
+        // a box without words takes up no space at all
+        if self.words.len() == words_before {
+          self.pending_opens -= 1;
+        } else {
+          self.words.last_mut().unwrap().closes += 1;
+        }
+        self.path.pop();
+      }
+    }
+  }
+
+  fn push_word(&mut self, text: String, style: &'a style::StyledNode<'a>) {
+    // whitespace at the start of the formatting context is dropped
+    let space_before = self.pending_space && !self.words.is_empty();
+    self.words.push(InlineWord {
+      text,
+      style,
+      space_before,
+      boxes: self.path.clone(),
+      opens: self.pending_opens,
+      closes: 0,
+    });
+    self.pending_space = false;
+    self.pending_opens = 0;
+  }
+}
+
+// turn a list of fragments into a line box at (x, y), with each
+// fragment's vertical position set relative to the shared baseline
+fn build_line<'a>(mut fragments: Vec<TextFragment<'a>>, x: f32, y: f32, width: f32) -> LineBox<'a> {
+  // the distance above and below the baseline each fragment
+  // needs, including half the leading on either side
+  let mut above = 0.0f32;
+  let mut below = 0.0f32;
+  for fragment in fragments.iter() {
+    let (ascent, descent, line_height) = font_metrics(fragment.font_size);
+    let half_leading = (line_height - (ascent + descent)) / 2.0;
+    above = above.max(ascent + half_leading);
+    below = below.max(descent + half_leading);
+  }
+
+  let baseline = y + above;
+  for fragment in fragments.iter_mut() {
+    let (ascent, descent, _) = font_metrics(fragment.font_size);
+    fragment.rect.x += x;
+    fragment.rect.y = baseline - ascent;
+    fragment.rect.height = ascent + descent;
+  }
+
+  LineBox {
+    rect: Rect { x, y, width, height: above + below },
+    baseline,
+    fragments,
+  }
+}
+
+// the padding, border and margin of an inline box
+// (percentages are relative to the containing block's width)
+fn inline_edges(style: &style::StyledNode, cb_width: f32) -> (EdgeSizes, EdgeSizes, EdgeSizes) {
+  let style = &style.computed;
+  let padding = EdgeSizes {
+    left: style.padding.left.to_px(cb_width),
+    right: style.padding.right.to_px(cb_width),
+    top: style.padding.top.to_px(cb_width),
+    bottom: style.padding.bottom.to_px(cb_width),
+  };
+  let border = EdgeSizes {
+    left: style.border_width.left,
+    right: style.border_width.right,
+    top: style.border_width.top,
+    bottom: style.border_width.bottom,
+  };
+  // vertical margins don't apply to inline boxes
+  let margin = EdgeSizes {
+    left: style.margin.left.to_px(cb_width),
+    right: style.margin.right.to_px(cb_width),
+    top: 0.0,
+    bottom: 0.0,
+  };
+  (padding, border, margin)
+}
+
+// font size in px
+fn font_size(style: &style::StyledNode) -> f32 {
+  style.computed.font_size
+}
+
+// width of `text` at `font_size`
+fn text_width(text: &str, font_size: f32) -> f32 {
+  font::Font::bundled().measure(text, font_size)
+}
+
+// (ascent, descent, line height) at `font_size`
+fn font_metrics(font_size: f32) -> (f32, f32, f32) {
+  let metrics = font::Font::bundled().metrics(font_size);
+  (metrics.ascent, metrics.descent, metrics.line_height)
+}
+
+// shorthand for a px length value
+fn px(f: f32) -> Length {
+  Length::Px(f)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::default::Default;
+  use super::{layout_tree, Dimensions, LayoutBox, Rect};
+  use crate::css;
+  use crate::html;
+  use crate::style;
+
+  // lay out `source` styled with `css` in an 800px wide viewport
+  // and pass the <body> box to `check`
+  fn with_layout<F>(source: &str, css: &str, check: F) where F: FnOnce(&LayoutBox) {
+    let css = format!("body {{ margin: 0px; }} {}", css);
+    let sheet = css::parse(css);
+    let dom = html::parse(source.to_string());
+    let styled = style::style_tree(&dom, &[(style::Origin::Author, &sheet)]);
+
+    let mut viewport: Dimensions = Default::default();
+    viewport.content.width = 800.0;
+
+    let root = layout_tree(&styled, viewport);
+    check(&root.children[0].children[0]);
+  }
+
+  #[test]
+  fn auto_width_fills_the_container() {
+    with_layout("<div></div>", "div { display: block; margin: 10px; padding: 5px; border-width: 1px; }", |root| {
+      let div = &root.children[0].dimensions;
+      assert_eq!(div.content.width, 800.0 - 2.0 * (10.0 + 5.0 + 1.0));
+      assert_eq!(div.content.x, 16.0);
+      assert_eq!(div.content.y, 16.0);
+      assert_eq!(div.margin_box().width, 800.0);
+    });
+  }
+
+  #[test]
+  fn shorthand_auto_margins_center_the_box() {
+    with_layout("<div></div>", "div { display: block; width: 200px; margin: 0 auto; }", |root| {
+      let div = &root.children[0].dimensions;
+      assert_eq!((div.margin.top, div.margin.bottom), (0.0, 0.0));
+      assert_eq!((div.margin.left, div.margin.right), (300.0, 300.0));
+      assert_eq!(div.content.x, 300.0);
+    });
+  }
+
+  #[test]
+  fn negative_margins_pull_the_box_out() {
+    with_layout("<div></div>", "div { display: block; margin-left: -10px; margin-top: -5px; }", |root| {
+      let div = &root.children[0].dimensions;
+      assert_eq!(div.margin.left, -10.0);
+      assert_eq!(div.content.x, -10.0);
+      assert_eq!(div.content.y, -5.0);
+      assert_eq!(div.content.width, 810.0);
+    });
+  }
+
+  #[test]
+  fn auto_margins_center_the_box() {
+    with_layout("<div></div>", "div { display: block; width: 200px; margin-left: auto; margin-right: auto; }", |root| {
+      let div = &root.children[0].dimensions;
+      assert_eq!(div.margin.left, 300.0);
+      assert_eq!(div.margin.right, 300.0);
+      assert_eq!(div.content.x, 300.0);
+    });
+  }
+
+  #[test]
+  fn over_constrained_width_adjusts_right_margin() {
+    with_layout("<div></div>", "div { display: block; width: 500px; margin-left: 100px; margin-right: 100px; }", |root| {
+      let div = &root.children[0].dimensions;
+      assert_eq!(div.margin.left, 100.0);
+      assert_eq!(div.margin.right, 200.0);
+    });
+  }
+
+  #[test]
+  fn percentage_width() {
+    with_layout("<div></div>", "div { display: block; width: 25%; }", |root| {
+      assert_eq!(root.children[0].dimensions.content.width, 200.0);
+    });
+  }
+
+  #[test]
+  fn blocks_stack_vertically() {
+    with_layout("<div></div><p></p>", "div, p { display: block; height: 30px; margin: 5px; }", |root| {
+      let div = &root.children[0].dimensions;
+      let p = &root.children[1].dimensions;
+      assert_eq!(div.content.y, 5.0);
+      assert_eq!(p.content.y, 45.0);
+      assert_eq!(root.dimensions.content.height, 80.0);
+    });
+  }
+
+  #[test]
+  fn display_none_is_skipped() {
+    with_layout("<div></div><p></p>", "div { display: none; } p { display: block; }", |root| {
+      assert_eq!(root.children.len(), 1);
+    });
+  }
+
+  #[test]
+  fn inline_text_wraps_into_lines() {
+    // the bundled font is 10px wide per char at 16px
+    with_layout("<p>aaaa bbbb cccc</p>", "p { display: block; width: 100px; margin: 0px; }", |root| {
+      let anonymous = &root.children[0].children[0];
+      assert_eq!(anonymous.lines.len(), 2);
+
+      let first = &anonymous.lines[0];
+      assert_eq!(first.fragments.len(), 2);
+      assert_eq!(first.fragments[0].text.as_str(), "aaaa");
+      assert_eq!(first.fragments[0].rect, Rect { x: 0.0, y: 0.0, width: 40.0, height: 19.0 });
+      assert_eq!(first.fragments[1].rect.x, 50.0);
+
+      let second = &anonymous.lines[1];
+      assert_eq!(second.fragments[0].text.as_str(), "cccc");
+      assert_eq!(second.fragments[0].rect.y, 19.0);
+      assert_eq!(anonymous.dimensions.content.height, 38.0);
+    });
+  }
+
+  #[test]
+  fn whitespace_collapses_across_elements() {
+    with_layout("<p>one   <span>two </span>  three</p>", "p { display: block; }", |root| {
+      let line = &root.children[0].children[0].lines[0];
+      let words: Vec<&str> = line.fragments.iter().map(|f| f.text.as_str()).collect();
+      assert_eq!(words, vec!["one", "two", "three"]);
+      assert_eq!(line.fragments[1].rect.x, 40.0);
+      assert_eq!(line.fragments[2].rect.x, 80.0);
+    });
+  }
+
+  #[test]
+  fn inline_boxes_cover_their_text_on_every_line() {
+    let css = "p { width: 100px; margin: 0px; }
+               span { background: #ff0000; padding-left: 5px; padding-right: 5px; border-width: 1px; }";
+    with_layout("<p>aa <span>bbbb cccc</span> dd</p>", css, |root| {
+      let anonymous = &root.children[0].children[0];
+      let span = &anonymous.children[1];
+      assert_eq!(span.fragments.len(), 2);
+
+      // the left edges only start the first line's piece...
+      let first = &span.fragments[0];
+      assert_eq!(first.content, Rect { x: 36.0, y: 0.0, width: 40.0, height: 19.0 });
+      assert_eq!((first.padding.left, first.padding.right), (5.0, 0.0));
+
+      // ...and the right ones only end the last, pushing "dd" over
+      let last = &span.fragments[1];
+      assert_eq!(last.content, Rect { x: 0.0, y: 19.0, width: 40.0, height: 19.0 });
+      assert_eq!((last.border.left, last.border.right), (0.0, 1.0));
+      assert_eq!(anonymous.lines[1].fragments[1].rect.x, 56.0);
+
+      assert_eq!(span.dimensions.content, Rect { x: 0.0, y: 0.0, width: 76.0, height: 38.0 });
+    });
+  }
+}
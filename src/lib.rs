@@ -0,0 +1,25 @@
+//! toy browser engine
+//!
+//! the pipeline runs `html::parse` and `css::parse`, matches the
+//! stylesheets against the DOM with `style::style_tree`, lays the styled
+//! tree out with `layout::layout_tree`, turns the layout tree into a
+//! display list with `painting::build_display_list` and finally draws it
+//! with `canvas::paint`.
+
+pub mod dom;
+pub mod html;
+pub mod tokenizer;
+pub mod css;
+pub mod style;
+pub mod properties;
+pub mod computed;
+pub mod layout;
+pub mod font;
+pub mod painting;
+pub mod canvas;
+pub mod png;
+
+mod entities;
+mod parser;
+
+pub use crate::parser::ParseError;
@@ -0,0 +1,312 @@
+//! command-line front end: renders an HTML file to an image,
+//! or dumps one of the stages of the pipeline
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::fs::File;
+use std::path::Path;
+use std::process;
+
+use toy_browser::{canvas, css, dom, html, layout, painting, style, ParseError};
+
+// options parsed from the command line
+struct Options {
+  html_file: String,
+  css_files: Vec<String>,
+  user_css_files: Vec<String>,
+  viewport_width: usize,
+  viewport_height: usize,
+  output: String,
+  dump: Option<Stage>,
+}
+
+// pipeline stage whose output is dumped with `--dump`
+#[derive(PartialEq)]
+enum Stage {
+  Dom,
+  Style,
+  Layout,
+  DisplayList,
+}
+
+// an error to report to the user, with the file (and line and
+// column, when we know them) that caused it
+struct CliError {
+  file: Option<String>,
+  position: Option<(usize, usize)>,
+  message: String,
+}
+
+impl fmt::Display for CliError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match (&self.file, self.position) {
+      (Some(file), Some((line, column))) =>
+        write!(f, "{}:{}:{}: {}", file, line, column, self.message),
+      (Some(file), None) => write!(f, "{}: {}", file, self.message),
+      _ => write!(f, "{}", self.message),
+    }
+  }
+}
+
+const USAGE: &str = "usage: toy-browser [options] <file.html>
+
+options:
+  --css <file>          add a stylesheet (may be repeated)
+  --user-css <file>     add a user stylesheet, which only overrides the
+                        page with `!important` (may be repeated)
+  --viewport <WxH>      viewport size in px (default: 800x600)
+  -o, --output <file>   where to write the rendered page; .ppm or .png
+                        (default: output.png)
+  --dump <stage>        print a pipeline stage instead of rendering:
+                        dom, style, layout or display-list
+  -h, --help            show this message";
+
+fn main() {
+  let args: Vec<String> = env::args().collect();
+
+  if args.iter().any(|arg| arg == "-h" || arg == "--help") {
+    println!("{}", USAGE);
+    return;
+  }
+
+  let result = parse_options(&args[1..]).and_then(|options| run(&options));
+
+  match result {
+    Ok(()) => {}
+    Err(error) => {
+      eprintln!("toy-browser: {}", error);
+      process::exit(1);
+    }
+  }
+}
+
+// run the whole pipeline, from html::parse to the rendered file
+fn run(options: &Options) -> Result<(), CliError> {
+  let html_source = read_file(&options.html_file)?;
+  let dom = parse_html(&options.html_file, html_source);
+
+  if options.dump == Some(Stage::Dom) {
+    dom.pretty_print(0);
+    return Ok(());
+  }
+
+  // user stylesheets, then the document's, then any extra ones
+  // in the order given
+  let mut stylesheets = Vec::new();
+  for css_file in options.user_css_files.iter() {
+    let css_source = read_file(css_file)?;
+    stylesheets.push((style::Origin::User, parse_css(css_file, css_source)));
+  }
+  if let dom::NodeType::Document(ref document) = dom.node_type {
+    for sheet in document.stylesheets().iter().filter(|sheet| sheet.applies_to_screen()) {
+      match sheet.source {
+        dom::StylesheetSource::Inline(ref sheet) => stylesheets.push((style::Origin::Author, sheet.clone())),
+        dom::StylesheetSource::Link(ref href) => {
+          if let Some(sheet) = load_linked_stylesheet(&options.html_file, href) {
+            stylesheets.push((style::Origin::Author, sheet));
+          }
+        }
+      }
+    }
+  }
+  for css_file in options.css_files.iter() {
+    let css_source = read_file(css_file)?;
+    stylesheets.push((style::Origin::Author, parse_css(css_file, css_source)));
+  }
+
+  let origin_sheets: Vec<style::OriginSheet> = stylesheets.iter()
+    .map(|&(origin, ref sheet)| (origin, sheet)).collect();
+  let style_root = style::style_tree(&dom, &origin_sheets);
+
+  if options.dump == Some(Stage::Style) {
+    print!("{}", style_root.dump());
+    return Ok(());
+  }
+
+  let mut viewport: layout::Dimensions = Default::default();
+  viewport.content.width = options.viewport_width as f32;
+  viewport.content.height = options.viewport_height as f32;
+
+  let layout_root = layout::layout_tree(&style_root, viewport);
+
+  if options.dump == Some(Stage::Layout) {
+    layout_root.pretty_print(0);
+    return Ok(());
+  }
+
+  let display_list = painting::build_display_list(&layout_root);
+
+  if options.dump == Some(Stage::DisplayList) {
+    print!("{}", painting::dump_display_list(&display_list));
+    return Ok(());
+  }
+
+  let canvas = canvas::paint(&display_list, options.viewport_width, options.viewport_height);
+  write_canvas(&canvas, &options.output)
+}
+
+// parse command line arguments (not including the program name)
+fn parse_options(args: &[String]) -> Result<Options, CliError> {
+  let mut options = Options {
+    html_file: String::new(),
+    css_files: Vec::new(),
+    user_css_files: Vec::new(),
+    viewport_width: 800,
+    viewport_height: 600,
+    output: "output.png".to_string(),
+    dump: None,
+  };
+  let mut html_file = None;
+
+  let mut i = 0;
+  while i < args.len() {
+    let arg = args[i].as_str();
+
+    // accept both `--flag value` and `--flag=value`
+    let (flag, inline_value) = match arg.find('=') {
+      Some(index) if arg.starts_with("--") => (&arg[..index], Some(&arg[index + 1..])),
+      _ => (arg, None),
+    };
+
+    let takes_value = matches!(flag, "--css" | "--user-css" | "--viewport" | "-o" | "--output" | "--dump");
+
+    let value = if !takes_value {
+      None
+    } else {
+      match inline_value {
+        Some(value) => Some(value.to_string()),
+        None if i + 1 < args.len() => {
+          i += 1;
+          Some(args[i].clone())
+        }
+        None => return Err(usage_error(format!("missing value for {}", flag))),
+      }
+    };
+
+    match (flag, value) {
+      ("--css", Some(file)) => options.css_files.push(file),
+      ("--user-css", Some(file)) => options.user_css_files.push(file),
+      ("--viewport", Some(size)) => {
+        let (width, height) = parse_viewport(&size)?;
+        options.viewport_width = width;
+        options.viewport_height = height;
+      }
+      ("-o", Some(file)) | ("--output", Some(file)) => options.output = file,
+      ("--dump", Some(stage)) => {
+        options.dump = Some(match stage.as_str() {
+          "dom" => Stage::Dom,
+          "style" => Stage::Style,
+          "layout" => Stage::Layout,
+          "display-list" => Stage::DisplayList,
+          _ => return Err(usage_error(format!("unknown stage \"{}\" for --dump", stage))),
+        });
+      }
+      _ if flag.starts_with('-') => return Err(usage_error(format!("unknown option {}", flag))),
+      _ => {
+        if html_file.is_some() {
+          return Err(usage_error("only one HTML file can be rendered at a time".to_string()));
+        }
+        html_file = Some(arg.to_string());
+      }
+    }
+
+    i += 1;
+  }
+
+  match html_file {
+    Some(file) => {
+      options.html_file = file;
+      Ok(options)
+    }
+    None => Err(usage_error("no HTML file given".to_string())),
+  }
+}
+
+// parse a viewport size like "800x600"
+fn parse_viewport(size: &str) -> Result<(usize, usize), CliError> {
+  let parts: Vec<&str> = size.split('x').collect();
+  if parts.len() == 2 {
+    match (parts[0].parse::<usize>(), parts[1].parse::<usize>()) {
+      (Ok(width), Ok(height)) if width > 0 && height > 0 => return Ok((width, height)),
+      _ => {}
+    }
+  }
+
+  Err(usage_error(format!("invalid viewport size \"{}\" (expected WxH, e.g. 800x600)", size)))
+}
+
+fn usage_error(message: String) -> CliError {
+  CliError {
+    file: None,
+    position: None,
+    message: format!("{}\n\n{}", message, USAGE),
+  }
+}
+
+fn read_file(path: &str) -> Result<String, CliError> {
+  fs::read_to_string(path).map_err(|error| CliError {
+    file: Some(path.to_string()),
+    position: None,
+    message: error.to_string(),
+  })
+}
+
+// turn a parse error into an error for `file`
+fn parse_error(file: &str, error: ParseError) -> CliError {
+  CliError {
+    file: Some(file.to_string()),
+    position: Some((error.line, error.column)),
+    message: format!("expected {}, found {}", error.expected, error.found),
+  }
+}
+
+// print the parts of `file` the parsers skipped over
+fn print_warnings(file: &str, warnings: Vec<ParseError>) {
+  for warning in warnings {
+    eprintln!("toy-browser: warning: {}", parse_error(file, warning));
+  }
+}
+
+fn parse_html(file: &str, source: String) -> dom::Node {
+  let (dom, warnings) = html::parse_with_warnings(source);
+  print_warnings(file, warnings);
+  dom
+}
+
+fn parse_css(file: &str, source: String) -> css::Stylesheet {
+  let (stylesheet, warnings) = css::parse_with_warnings(source);
+  print_warnings(file, warnings);
+  stylesheet
+}
+
+// load a <link>ed stylesheet from a path relative to the HTML
+// file; one that can't be read is skipped, as a browser would
+fn load_linked_stylesheet(html_file: &str, href: &str) -> Option<css::Stylesheet> {
+  let path = Path::new(html_file).with_file_name(href);
+  let path = path.to_string_lossy();
+  match read_file(&path) {
+    Ok(source) => Some(parse_css(&path, source)),
+    Err(error) => {
+      eprintln!("toy-browser: warning: {}", error);
+      None
+    }
+  }
+}
+
+// write the canvas in the format given by the file extension
+fn write_canvas(canvas: &canvas::Canvas, path: &str) -> Result<(), CliError> {
+  let result = File::create(path).and_then(|mut file| {
+    if path.ends_with(".ppm") {
+      canvas.write_ppm(&mut file)
+    } else {
+      canvas.write_png(&mut file)
+    }
+  });
+
+  result.map_err(|error| CliError {
+    file: Some(path.to_string()),
+    position: None,
+    message: error.to_string(),
+  })
+}
@@ -0,0 +1,212 @@
+//! painting: turns a layout tree into a display list
+//! that a backend can draw
+
+use std::fmt;
+use crate::computed::Overflow;
+use crate::css::Color;
+use crate::layout::{LayoutBox, BoxType, Dimensions, Rect, EdgeSizes};
+use crate::style;
+
+pub type DisplayList = Vec<DisplayCommand>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisplayCommand {
+  // fill `Rect` with `Color`
+  SolidColor(Rect, Color),
+  // draw the edges of a border box `Rect`, with widths `EdgeSizes`
+  Border(Rect, EdgeSizes, Color),
+  // draw `GlyphRun` with its baseline starting at (x, y)
+  Text(GlyphRun, f32, f32, Color),
+  // clip everything up to the matching PopClip to `Rect`
+  PushClip(Rect),
+  PopClip,
+}
+
+// a run of text in a single font size
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlyphRun {
+  pub text: String,
+  pub font_size: f32,
+}
+
+// one command per line, e.g. "solid-color (8, 8, 784, 40) #ff0000ff"
+impl fmt::Display for DisplayCommand {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      DisplayCommand::SolidColor(ref rect, ref color) =>
+        write!(f, "solid-color {} {}", show_rect(rect), show_color(color)),
+      DisplayCommand::Border(ref rect, ref widths, ref color) =>
+        write!(f, "border {} ({}, {}, {}, {}) {}", show_rect(rect),
+               widths.top, widths.right, widths.bottom, widths.left, show_color(color)),
+      DisplayCommand::Text(ref run, x, y, ref color) =>
+        write!(f, "text \"{}\" {}px ({}, {}) {}", run.text.escape_default(), run.font_size, x, y, show_color(color)),
+      DisplayCommand::PushClip(ref rect) =>
+        write!(f, "push-clip {}", show_rect(rect)),
+      DisplayCommand::PopClip =>
+        write!(f, "pop-clip"),
+    }
+  }
+}
+
+// build a display list for the whole layout tree,
+// in painting order (parents before children)
+pub fn build_display_list(layout_root: &LayoutBox) -> DisplayList {
+  let mut list = Vec::new();
+  render_layout_box(&mut list, layout_root);
+  list
+}
+
+// dump a display list, one command per line
+pub fn dump_display_list(list: &DisplayList) -> String {
+  let mut result = String::new();
+  for command in list.iter() {
+    result.push_str(&format!("{}\n", command));
+  }
+  result
+}
+
+fn render_layout_box(list: &mut DisplayList, layout_box: &LayoutBox) {
+  render_background(list, layout_box);
+  render_borders(list, layout_box);
+
+  // `overflow: hidden` clips descendants to the padding box
+  let clip = get_style_node(layout_box).is_some_and(|style| style.computed.overflow == Overflow::Hidden);
+
+  if clip {
+    list.push(DisplayCommand::PushClip(layout_box.dimensions.padding_box()));
+  }
+
+  for child in layout_box.children.iter() {
+    render_layout_box(list, child);
+  }
+
+  // lines go on top of the backgrounds of the inline boxes in them
+  render_text(list, layout_box);
+
+  if clip {
+    list.push(DisplayCommand::PopClip);
+  }
+}
+
+// the areas to paint a box's background and borders in: one
+// per line for inline boxes, otherwise just its dimensions
+fn box_areas<'b>(layout_box: &'b LayoutBox) -> &'b [Dimensions] {
+  match layout_box.box_type {
+    BoxType::InlineNode(_) => &layout_box.fragments,
+    _ => std::slice::from_ref(&layout_box.dimensions),
+  }
+}
+
+fn render_background(list: &mut DisplayList, layout_box: &LayoutBox) {
+  // nothing to draw for a transparent background
+  if let Some(style) = get_style_node(layout_box).filter(|style| style.computed.background_color.a > 0) {
+    for d in box_areas(layout_box).iter() {
+      list.push(DisplayCommand::SolidColor(d.border_box(), style.computed.background_color.clone()));
+    }
+  }
+}
+
+fn render_borders(list: &mut DisplayList, layout_box: &LayoutBox) {
+  let color = get_style_node(layout_box).map_or(Color { r: 0, g: 0, b: 0, a: 255 },
+                                                |style| style.computed.border_color.clone());
+
+  for d in box_areas(layout_box).iter() {
+    let border = &d.border;
+    if border.top == 0.0 && border.right == 0.0 && border.bottom == 0.0 && border.left == 0.0 {
+      continue;
+    }
+    list.push(DisplayCommand::Border(d.border_box(), border.clone(), color.clone()));
+  }
+}
+
+fn render_text(list: &mut DisplayList, layout_box: &LayoutBox) {
+  for line in layout_box.lines.iter() {
+    for fragment in line.fragments.iter() {
+      let color = fragment.style.computed.color.clone();
+
+      let run = GlyphRun {
+        text: fragment.text.clone(),
+        font_size: fragment.font_size,
+      };
+      list.push(DisplayCommand::Text(run, fragment.rect.x, line.baseline, color));
+    }
+  }
+}
+
+fn get_style_node<'a>(layout_box: &LayoutBox<'a>) -> Option<&'a style::StyledNode<'a>> {
+  match layout_box.box_type {
+    BoxType::BlockNode(node) | BoxType::InlineNode(node) => Some(node),
+    BoxType::AnonymousBlock => None,
+  }
+}
+
+fn show_rect(rect: &Rect) -> String {
+  format!("({}, {}, {}, {})", rect.x, rect.y, rect.width, rect.height)
+}
+
+fn show_color(color: &Color) -> String {
+  format!("#{:02x}{:02x}{:02x}{:02x}", color.r, color.g, color.b, color.a)
+}
+
+#[cfg(test)]
+mod tests {
+  use std::default::Default;
+  use super::{build_display_list, dump_display_list};
+  use crate::css;
+  use crate::html;
+  use crate::layout;
+  use crate::style;
+
+  #[test]
+  fn paints_backgrounds_borders_text_and_clips() {
+    let sheet = css::parse("
+      body { margin: 0px; }
+      div { display: block; width: 100px; padding: 2px; border-width: 1px;
+            border-color: #0000ff; background: #eeeeee; overflow: hidden; }
+      span { color: #ff0000; }".to_string());
+    let dom = html::parse("<div><span>hi</span></div>".to_string());
+    let styled = style::style_tree(&dom, &[(style::Origin::Author, &sheet)]);
+
+    let mut viewport: layout::Dimensions = Default::default();
+    viewport.content.width = 800.0;
+    let layout_root = layout::layout_tree(&styled, viewport);
+
+    assert_eq!(dump_display_list(&build_display_list(&layout_root)),
+"solid-color (0, 0, 106, 25) #eeeeeeff
+border (0, 0, 106, 25) (1, 1, 1, 1) #0000ffff
+push-clip (1, 1, 104, 23)
+text \"hi\" 16px (3, 18) #ff0000ff
+pop-clip
+");
+  }
+
+  #[test]
+  fn paints_inline_backgrounds_under_their_text() {
+    let sheet = css::parse("body { margin: 0px; } span { background: #00ff00; }".to_string());
+    let dom = html::parse("<p>a <span>b</span></p>".to_string());
+    let styled = style::style_tree(&dom, &[(style::Origin::Author, &sheet)]);
+
+    let mut viewport: layout::Dimensions = Default::default();
+    viewport.content.width = 800.0;
+    let layout_root = layout::layout_tree(&styled, viewport);
+
+    assert_eq!(dump_display_list(&build_display_list(&layout_root)),
+"solid-color (20, 16, 10, 19) #00ff00ff
+text \"a\" 16px (0, 31) #000000ff
+text \"b\" 16px (20, 31) #000000ff
+");
+  }
+
+  #[test]
+  fn escapes_text_in_the_dump() {
+    let dom = html::parse(r#"<p>"a\b"</p>"#.to_string());
+    let styled = style::style_tree(&dom, &[]);
+
+    let mut viewport: layout::Dimensions = Default::default();
+    viewport.content.width = 800.0;
+    let layout_root = layout::layout_tree(&styled, viewport);
+
+    let dump = dump_display_list(&build_display_list(&layout_root));
+    assert!(dump.starts_with(r#"text "\"a\\b\"" 16px"#), "{}", dump);
+  }
+}
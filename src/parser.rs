@@ -0,0 +1,149 @@
+//! generic parser structure and functionality to be
+//! extended by other (e.g., css, html) parsers
+
+use std::error::Error;
+use std::fmt;
+
+pub struct Parser {
+  pub pos: usize,
+  pub input: String,
+}
+
+// an error at a position in the parser's input
+// (`line` and `column` count from 1, columns in chars)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+  pub line: usize,
+  pub column: usize,
+  pub byte_offset: usize,
+  pub expected: String,
+  pub found: String,
+}
+
+impl fmt::Display for ParseError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "line {}, column {}: expected {}, found {}",
+           self.line, self.column, self.expected, self.found)
+  }
+}
+
+impl Error for ParseError {}
+
+impl Parser {
+  pub fn new(input: String) -> Parser {
+    Parser { pos: 0, input }
+  }
+
+  // read & return next char
+  pub fn next_char(&self) -> char {
+    self.input[self.pos..].chars().next().unwrap()
+  }
+
+  // do the next chars start with string `s`?
+  pub fn starts_with(&self, s: &str) -> bool {
+    self.input[self.pos..].starts_with(s)
+  }
+
+  // have we consumed all the input?
+  pub fn eof(&self) -> bool {
+    self.pos >= self.input.len()
+  }
+
+  // return current char and advance to the next
+  pub fn consume_char(&mut self) -> char {
+    let c = self.next_char();
+    self.pos += c.len_utf8();
+
+    c
+  }
+
+  // consume chars until `test` -> false
+  pub fn consume_while<F>(&mut self, test: F) -> String
+    where F: Fn(char) -> bool {
+    let mut result = String::new();
+    while !self.eof() && test(self.next_char()) {
+      result.push(self.consume_char());
+    }
+
+    result
+  }
+
+  // consume and throw out whitespace
+  pub fn consume_whitespace(&mut self) {
+    self.consume_while(|c| c.is_whitespace());
+  }
+
+  // consume `s`, or fail if the input doesn't continue with it
+  pub fn expect(&mut self, s: &str) -> Result<(), ParseError> {
+    if self.starts_with(s) {
+      self.pos += s.len();
+      Ok(())
+    } else {
+      Err(self.error(&format!("`{}`", s)))
+    }
+  }
+
+  // describe the next char for an error message
+  pub fn found(&self) -> String {
+    if self.eof() {
+      "end of input".to_string()
+    } else {
+      format!("`{}`", self.next_char())
+    }
+  }
+
+  // an error at the current position, with whatever is
+  // there as what was found
+  pub fn error(&self, expected: &str) -> ParseError {
+    self.error_at(self.pos, expected, &self.found())
+  }
+
+  // an error at `byte_offset`
+  pub fn error_at(&self, byte_offset: usize, expected: &str, found: &str) -> ParseError {
+    let (line, column) = self.line_and_column(byte_offset);
+    ParseError {
+      line,
+      column,
+      byte_offset,
+      expected: expected.to_string(),
+      found: found.to_string(),
+    }
+  }
+
+  // line and column of `byte_offset`, counting from 1
+  pub fn line_and_column(&self, byte_offset: usize) -> (usize, usize) {
+    let before = &self.input[..byte_offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Parser;
+
+  #[test]
+  fn errors_report_line_and_column() {
+    let mut p = Parser::new("ab\ncdé\nfg".to_string());
+    p.consume_while(|c| c != 'f');
+    assert!(p.expect("fx").is_err());
+
+    let error = p.error("`x`");
+    assert_eq!((error.line, error.column, error.byte_offset), (3, 1, 8));
+    assert_eq!(error.found, "`f`");
+    assert_eq!(p.line_and_column(5), (2, 3));
+  }
+
+  #[test]
+  fn expect_consumes_on_success() {
+    let mut p = Parser::new("<!--x".to_string());
+    assert!(p.expect("<!--").is_ok());
+    assert_eq!(p.next_char(), 'x');
+
+    p.consume_char();
+    let error = p.expect(">").unwrap_err();
+    assert_eq!(error.found, "end of input");
+  }
+}
@@ -0,0 +1,358 @@
+//! minimal PNG encoder
+//! (8-bit RGBA only, compressed with a small hand-written deflate)
+
+use std::io;
+use std::io::Write;
+
+// write `rgba` (4 bytes per pixel, row by row) as a PNG image
+pub fn encode<W: Write>(w: &mut W, width: usize, height: usize, rgba: &[u8]) -> io::Result<()> {
+  assert!(rgba.len() == width * height * 4);
+
+  w.write_all(&[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a])?;
+
+  let mut header = Vec::new();
+  push_u32(&mut header, width as u32);
+  push_u32(&mut header, height as u32);
+  header.extend_from_slice(&[
+    8, // bit depth
+    6, // color type: RGBA
+    0, // compression: deflate
+    0, // filter method: adaptive
+    0, // interlace: none
+  ]);
+  write_chunk(w, b"IHDR", &header)?;
+
+  // every scanline starts with its filter type (0: none)
+  let stride = width * 4;
+  let mut raw = Vec::with_capacity((stride + 1) * height);
+  for y in 0..height {
+    raw.push(0u8);
+    raw.extend_from_slice(&rgba[y * stride..(y + 1) * stride]);
+  }
+  write_chunk(w, b"IDAT", &zlib_compress(&raw))?;
+
+  write_chunk(w, b"IEND", &[])
+}
+
+// write a chunk: length, type, data, CRC of type and data
+fn write_chunk<W: Write>(w: &mut W, chunk_type: &[u8], data: &[u8]) -> io::Result<()> {
+  let mut chunk = Vec::with_capacity(data.len() + 12);
+  push_u32(&mut chunk, data.len() as u32);
+  chunk.extend_from_slice(chunk_type);
+  chunk.extend_from_slice(data);
+  let crc = crc32(&chunk[4..]);
+  push_u32(&mut chunk, crc);
+
+  w.write_all(&chunk)
+}
+
+fn push_u32(buf: &mut Vec<u8>, n: u32) {
+  buf.push((n >> 24) as u8);
+  buf.push((n >> 16) as u8);
+  buf.push((n >> 8) as u8);
+  buf.push(n as u8);
+}
+
+// CRC-32 as used by PNG (ISO 3309)
+fn crc32(data: &[u8]) -> u32 {
+  let mut table = [0u32; 256];
+  for (n, entry) in table.iter_mut().enumerate() {
+    let mut c = n as u32;
+    for _ in 0..8 {
+      c = if c & 1 != 0 { 0xedb88320 ^ (c >> 1) } else { c >> 1 };
+    }
+    *entry = c;
+  }
+
+  let mut crc = 0xffffffffu32;
+  for &byte in data.iter() {
+    crc = table[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8);
+  }
+  crc ^ 0xffffffff
+}
+
+// Adler-32 checksum, as used by zlib
+fn adler32(data: &[u8]) -> u32 {
+  let mut a = 1u32;
+  let mut b = 0u32;
+  for &byte in data.iter() {
+    a = (a + byte as u32) % 65521;
+    b = (b + a) % 65521;
+  }
+  (b << 16) | a
+}
+
+// writes bits least significant first, as deflate expects
+struct BitWriter {
+  bytes: Vec<u8>,
+  bit_buffer: u32,
+  bit_count: usize,
+}
+
+impl BitWriter {
+  fn write_bits(&mut self, value: u32, count: usize) {
+    self.bit_buffer |= value << self.bit_count;
+    self.bit_count += count;
+    while self.bit_count >= 8 {
+      self.bytes.push(self.bit_buffer as u8);
+      self.bit_buffer >>= 8;
+      self.bit_count -= 8;
+    }
+  }
+
+  // huffman codes are packed starting from their most significant bit
+  fn write_code(&mut self, code: u32, length: usize) {
+    let mut reversed = 0u32;
+    for i in 0..length {
+      reversed |= ((code >> i) & 1) << (length - 1 - i);
+    }
+    self.write_bits(reversed, length);
+  }
+
+  fn flush(&mut self) {
+    if self.bit_count > 0 {
+      self.bytes.push(self.bit_buffer as u8);
+      self.bit_buffer = 0;
+      self.bit_count = 0;
+    }
+  }
+}
+
+const LENGTH_BASE: [u32; 29] = [
+  3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31,
+  35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [usize; 29] = [
+  0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2,
+  3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+const DIST_BASE: [u32; 30] = [
+  1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193,
+  257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577];
+const DIST_EXTRA: [usize; 30] = [
+  0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6,
+  7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+const WINDOW_SIZE: usize = 32768;
+const MAX_MATCH: usize = 258;
+const HASH_SIZE: usize = 1 << 15;
+
+// compress `data` into a zlib stream: a single deflate block using
+// the fixed huffman codes, with greedy LZ77 matching
+pub fn zlib_compress(data: &[u8]) -> Vec<u8> {
+  let mut out = BitWriter { bytes: vec![0x78, 0x9c], bit_buffer: 0, bit_count: 0 };
+
+  out.write_bits(1, 1); // final block
+  out.write_bits(1, 2); // fixed huffman codes
+
+  // most recent position of each 3-byte sequence
+  let mut head = vec![-1isize; HASH_SIZE];
+
+  let mut pos = 0;
+  while pos < data.len() {
+    let mut match_len = 0;
+    let mut match_dist = 0;
+
+    if pos + 3 <= data.len() {
+      let hash = hash3(data, pos);
+      let candidate = head[hash];
+      if candidate >= 0 && pos - candidate as usize <= WINDOW_SIZE {
+        let start = candidate as usize;
+        let max = if data.len() - pos < MAX_MATCH { data.len() - pos } else { MAX_MATCH };
+        while match_len < max && data[start + match_len] == data[pos + match_len] {
+          match_len += 1;
+        }
+        match_dist = pos - start;
+      }
+      head[hash] = pos as isize;
+    }
+
+    if match_len >= 3 {
+      write_length(&mut out, match_len as u32);
+      write_distance(&mut out, match_dist as u32);
+
+      // keep the hash table up to date for the skipped bytes
+      for i in pos + 1..pos + match_len {
+        if i + 3 <= data.len() {
+          head[hash3(data, i)] = i as isize;
+        }
+      }
+      pos += match_len;
+    } else {
+      write_literal(&mut out, data[pos] as u32);
+      pos += 1;
+    }
+  }
+
+  write_literal(&mut out, 256); // end of block
+  out.flush();
+
+  let checksum = adler32(data);
+  push_u32(&mut out.bytes, checksum);
+  out.bytes
+}
+
+fn hash3(data: &[u8], pos: usize) -> usize {
+  let n = ((data[pos] as usize) << 16) | ((data[pos + 1] as usize) << 8) | data[pos + 2] as usize;
+  (n.wrapping_mul(2654435761) >> 17) & (HASH_SIZE - 1)
+}
+
+// write a literal/length symbol using the fixed huffman code
+fn write_literal(out: &mut BitWriter, symbol: u32) {
+  match symbol {
+    0..=143 => out.write_code(0x30 + symbol, 8),
+    144..=255 => out.write_code(0x190 + symbol - 144, 9),
+    256..=279 => out.write_code(symbol - 256, 7),
+    _ => out.write_code(0xc0 + symbol - 280, 8),
+  }
+}
+
+fn write_length(out: &mut BitWriter, length: u32) {
+  let mut code = 0;
+  while code + 1 < LENGTH_BASE.len() && LENGTH_BASE[code + 1] <= length {
+    code += 1;
+  }
+  write_literal(out, 257 + code as u32);
+  out.write_bits(length - LENGTH_BASE[code], LENGTH_EXTRA[code]);
+}
+
+fn write_distance(out: &mut BitWriter, distance: u32) {
+  let mut code = 0;
+  while code + 1 < DIST_BASE.len() && DIST_BASE[code + 1] <= distance {
+    code += 1;
+  }
+  out.write_code(code as u32, 5);
+  out.write_bits(distance - DIST_BASE[code], DIST_EXTRA[code]);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{crc32, adler32, encode, zlib_compress, DIST_BASE, DIST_EXTRA, LENGTH_BASE, LENGTH_EXTRA};
+
+  // reads bits least significant first, mirroring BitWriter
+  struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+  }
+
+  impl BitReader<'_> {
+    fn read_bits(&mut self, count: usize) -> u32 {
+      let mut value = 0;
+      for i in 0..count {
+        let bit = (self.bytes[self.pos / 8] >> (self.pos % 8)) & 1;
+        value |= (bit as u32) << i;
+        self.pos += 1;
+      }
+      value
+    }
+
+    // huffman codes come most significant bit first
+    fn read_code(&mut self, length: usize) -> u32 {
+      (0..length).fold(0, |code, _| (code << 1) | self.read_bits(1))
+    }
+
+    // decode a literal/length symbol with the fixed huffman code
+    fn read_literal(&mut self) -> u32 {
+      let code = self.read_code(7);
+      if code <= 0x17 {
+        return 256 + code;
+      }
+      let code = (code << 1) | self.read_bits(1);
+      match code {
+        0x30..=0xbf => code - 0x30,
+        0xc0..=0xc7 => 280 + code - 0xc0,
+        _ => 144 + ((code << 1) | self.read_bits(1)) - 0x190,
+      }
+    }
+  }
+
+  // just enough of an inflater to read back what zlib_compress
+  // writes: one final block with the fixed huffman codes
+  fn zlib_decompress(stream: &[u8]) -> Vec<u8> {
+    assert_eq!(&stream[..2], &[0x78, 0x9c]);
+    let mut reader = BitReader { bytes: &stream[2..stream.len() - 4], pos: 0 };
+    assert_eq!(reader.read_bits(1), 1, "final block");
+    assert_eq!(reader.read_bits(2), 1, "fixed huffman codes");
+
+    let mut out: Vec<u8> = Vec::new();
+    loop {
+      match reader.read_literal() {
+        literal @ 0..=255 => out.push(literal as u8),
+        256 => break,
+        symbol => {
+          let code = (symbol - 257) as usize;
+          let length = LENGTH_BASE[code] + reader.read_bits(LENGTH_EXTRA[code]);
+          let code = reader.read_code(5) as usize;
+          let distance = DIST_BASE[code] + reader.read_bits(DIST_EXTRA[code]);
+          for _ in 0..length {
+            out.push(out[out.len() - distance as usize]);
+          }
+        }
+      }
+    }
+
+    let checksum = u32::from_be_bytes(stream[stream.len() - 4..].try_into().unwrap());
+    assert_eq!(checksum, adler32(&out));
+    out
+  }
+
+  #[test]
+  fn checksums() {
+    assert_eq!(crc32(b"IEND"), 0xae426082);
+    assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+  }
+
+  #[test]
+  fn encodes_header_and_chunks() {
+    let mut out = Vec::new();
+    encode(&mut out, 2, 1, &[255, 0, 0, 255, 0, 0, 255, 128]).unwrap();
+
+    assert_eq!(&out[0..8], &[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a]);
+    assert_eq!(&out[12..16], b"IHDR");
+    assert_eq!(&out[out.len() - 12..],
+               &[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]);
+  }
+
+  #[test]
+  fn compressed_data_inflates_back() {
+    // runs long enough for several maximum-length matches, then
+    // noise that mostly stays literal
+    let mut data = vec![7u8; 1000];
+    data.extend(b"abcabcabcd".iter().cycle().take(300));
+    let mut seed = 12345u32;
+    data.extend((0..2000).map(|_| {
+      seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+      (seed >> 16) as u8
+    }));
+    data.extend_from_within(10..400);
+
+    assert_eq!(zlib_decompress(&zlib_compress(&data)), data);
+    assert_eq!(zlib_decompress(&zlib_compress(&[])), Vec::<u8>::new());
+  }
+
+  #[test]
+  fn image_data_holds_the_filtered_scanlines() {
+    let (width, height) = (3, 40);
+    let rgba: Vec<u8> = (0..width * height * 4).map(|i| (i % 7 * 30) as u8).collect();
+    let mut out = Vec::new();
+    encode(&mut out, width, height, &rgba).unwrap();
+
+    // walk the chunks after the signature to find IDAT
+    let mut pos = 8;
+    let mut idat = None;
+    while pos < out.len() {
+      let length = u32::from_be_bytes(out[pos..pos + 4].try_into().unwrap()) as usize;
+      let chunk = &out[pos + 4..pos + 8 + length];
+      let crc = u32::from_be_bytes(out[pos + 8 + length..pos + 12 + length].try_into().unwrap());
+      assert_eq!(crc, crc32(chunk));
+      if &chunk[..4] == b"IDAT" {
+        idat = Some(&chunk[4..]);
+      }
+      pos += length + 12;
+    }
+
+    // each scanline is filter type 0 followed by the row's pixels
+    let scanlines: Vec<u8> = rgba.chunks(width * 4)
+      .flat_map(|row| std::iter::once(0).chain(row.iter().cloned()))
+      .collect();
+    assert_eq!(zlib_decompress(idat.unwrap()), scanlines);
+  }
+}
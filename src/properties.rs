@@ -0,0 +1,126 @@
+//! the CSS properties the engine knows about: whether each one
+//! is inherited, and its initial value
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use crate::css;
+
+pub struct Property {
+  pub name: &'static str,
+  pub inherited: bool,
+  // written as CSS, and parsed by `initial_values`
+  pub initial: &'static str,
+}
+
+// every longhand property, sorted by name
+// (borders have no style yet, so their widths start at 0)
+pub const PROPERTIES: &[Property] = &[
+  Property { name: "background-color", inherited: false, initial: "transparent" },
+  Property { name: "border-bottom-width", inherited: false, initial: "0px" },
+  Property { name: "border-color", inherited: false, initial: "currentcolor" },
+  Property { name: "border-left-width", inherited: false, initial: "0px" },
+  Property { name: "border-right-width", inherited: false, initial: "0px" },
+  Property { name: "border-top-width", inherited: false, initial: "0px" },
+  Property { name: "color", inherited: true, initial: "#000000" },
+  Property { name: "display", inherited: false, initial: "inline" },
+  Property { name: "font-family", inherited: true, initial: "serif" },
+  Property { name: "font-size", inherited: true, initial: "medium" },
+  Property { name: "font-style", inherited: true, initial: "normal" },
+  Property { name: "font-weight", inherited: true, initial: "normal" },
+  Property { name: "height", inherited: false, initial: "auto" },
+  Property { name: "list-style-type", inherited: true, initial: "disc" },
+  Property { name: "margin-bottom", inherited: false, initial: "0px" },
+  Property { name: "margin-left", inherited: false, initial: "0px" },
+  Property { name: "margin-right", inherited: false, initial: "0px" },
+  Property { name: "margin-top", inherited: false, initial: "0px" },
+  Property { name: "overflow", inherited: false, initial: "visible" },
+  Property { name: "padding-bottom", inherited: false, initial: "0px" },
+  Property { name: "padding-left", inherited: false, initial: "0px" },
+  Property { name: "padding-right", inherited: false, initial: "0px" },
+  Property { name: "padding-top", inherited: false, initial: "0px" },
+  Property { name: "width", inherited: false, initial: "auto" },
+];
+
+// look up a longhand property by name
+pub fn property(name: &str) -> Option<&'static Property> {
+  PROPERTIES.binary_search_by_key(&name, |property| property.name).ok().map(|index| &PROPERTIES[index])
+}
+
+// the initial value of every property, parsed on first use
+pub fn initial_values() -> &'static HashMap<String, css::Value> {
+  static VALUES: OnceLock<HashMap<String, css::Value>> = OnceLock::new();
+  VALUES.get_or_init(|| {
+    PROPERTIES.iter().map(|property| {
+      (property.name.to_string(), css::parse_value(property.initial).unwrap())
+    }).collect()
+  })
+}
+
+// the longhands a shorthand sets; empty for anything that isn't
+// a shorthand
+pub fn longhands(name: &str) -> &'static [&'static str] {
+  match name {
+    "margin" => &["margin-top", "margin-right", "margin-bottom", "margin-left"],
+    "padding" => &["padding-top", "padding-right", "padding-bottom", "padding-left"],
+    "border-width" => &["border-top-width", "border-right-width", "border-bottom-width", "border-left-width"],
+    "background" => &["background-color"],
+    _ => &[],
+  }
+}
+
+// does `name` set the four sides of a box, from one to four values?
+pub fn is_box_shorthand(name: &str) -> bool {
+  matches!(name, "margin" | "padding" | "border-width")
+}
+
+// the value a shorthand's value gives its `index`th longhand; a
+// list of side values repeats as in CSS (top, right, bottom, left,
+// with missing sides copying the opposite one)
+pub fn longhand_value(value: &css::Value, index: usize) -> css::Value {
+  match *value {
+    css::Value::List(ref values) => {
+      // which value each side takes, by how many there are
+      const SIDES: [[usize; 4]; 4] = [[0, 0, 0, 0], [0, 1, 0, 1], [0, 1, 2, 1], [0, 1, 2, 3]];
+      values[SIDES[values.len() - 1][index]].clone()
+    }
+    _ => value.clone(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{initial_values, longhand_value, longhands, property, PROPERTIES};
+  use crate::css::{self, Color, Value};
+
+  #[test]
+  fn table_is_sorted_and_parses() {
+    assert!(PROPERTIES.windows(2).all(|pair| pair[0].name < pair[1].name));
+    assert_eq!(initial_values().len(), PROPERTIES.len());
+    assert_eq!(initial_values()["color"], Value::ColorValue(Color { r: 0, g: 0, b: 0, a: 255 }));
+
+    // shorthands expand into properties in the table
+    for shorthand in ["margin", "padding", "border-width", "background"].iter() {
+      assert!(longhands(shorthand).iter().all(|name| property(name).is_some()));
+    }
+  }
+
+  #[test]
+  fn looks_up_properties() {
+    assert!(property("color").unwrap().inherited);
+    assert!(!property("width").unwrap().inherited);
+    assert!(property("margin").is_none());
+  }
+
+  #[test]
+  fn side_values_repeat_as_in_css() {
+    let expand = |values: &[&str]| -> Vec<String> {
+      let mut values: Vec<Value> = values.iter().map(|value| css::parse_value(value).unwrap()).collect();
+      let value = if values.len() == 1 { values.pop().unwrap() } else { Value::List(values) };
+      (0..4).map(|index| longhand_value(&value, index).to_string()).collect()
+    };
+    assert_eq!(expand(&["1px"]), vec!["1px", "1px", "1px", "1px"]);
+    assert_eq!(expand(&["1px", "auto"]), vec!["1px", "auto", "1px", "auto"]);
+    assert_eq!(expand(&["1px", "2px", "3px"]), vec!["1px", "2px", "3px", "2px"]);
+    assert_eq!(expand(&["1px", "2px", "3px", "4px"]), vec!["1px", "2px", "3px", "4px"]);
+  }
+}
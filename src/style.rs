@@ -0,0 +1,609 @@
+//! matches DOM tree to style tree
+//! (lots of real-browser stuff not implemented)
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use crate::computed::{self, ComputedStyle};
+use crate::css;
+use crate::dom;
+use crate::layout;
+use crate::properties;
+
+pub type PropertyMap = HashMap<String, css::Value>;
+pub type MatchedRule<'a> = (css::Specificity, &'a css::Rule);
+
+// a stylesheet and where it came from, as given to `style_tree`
+pub type OriginSheet<'a> = (Origin, &'a css::Stylesheet);
+
+// the origins of the cascade, in order of precedence
+// for normal (not `!important`) declarations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Origin {
+  UserAgent,
+  User,
+  Author,
+}
+
+#[derive(Debug)]
+pub struct StyledNode<'a> {
+  pub node: &'a dom::Node,
+  pub specified_values: PropertyMap,
+  // the specified values, resolved
+  pub computed: ComputedStyle,
+  pub children: Vec<StyledNode<'a>>,
+}
+
+impl<'a> StyledNode<'a> {
+  // get value of `name` if it has it
+  pub fn value(&self, name: &str) -> Option<css::Value> {
+    self.specified_values.get(name).cloned()
+  }
+
+  // get value of `name`, falling back to `fallback_name`
+  // and then to `default`
+  pub fn lookup(&self, name: &str, fallback_name: &str, default: &css::Value) -> css::Value {
+    self.value(name).unwrap_or_else(|| self.value(fallback_name)
+                    .unwrap_or_else(|| default.clone()))
+  }
+
+  // dump the style tree as text: one node per line, followed by
+  // the specified values that aren't initial, sorted by name
+  pub fn dump(&self) -> String {
+    let mut out = String::new();
+    self.dump_into(&mut out, 0);
+    out
+  }
+
+  fn dump_into(&self, out: &mut String, indent_level: usize) {
+    let mut spaces = String::new();
+    let mut counter = 0;
+
+    loop {
+      if counter == indent_level { break }
+      counter += 1;
+      spaces.push_str("  ");
+    }
+
+    out.push_str(&format!("{}{}", spaces, self.node.node_type));
+
+    let initial_values = properties::initial_values();
+    let mut names: Vec<&String> = self.specified_values.iter()
+      .filter(|&(name, value)| initial_values.get(name) != Some(value))
+      .map(|(name, _)| name).collect();
+    names.sort();
+    if !names.is_empty() {
+      out.push_str(" {");
+      for name in names.iter() {
+        out.push_str(&format!(" {}: {};", name, self.specified_values[*name]));
+      }
+      out.push_str(" }");
+    }
+    out.push('\n');
+
+    for child in self.children.iter() {
+      child.dump_into(out, indent_level + 1);
+    }
+  }
+
+  // get the computed 'display' value
+  pub fn display(&self) -> layout::Display {
+    self.computed.display
+  }
+}
+
+// does `elem` match `selector`? compound selectors are
+// matched right to left, trying every element the combinator
+// allows for the rest of the selector. `scope` is the element
+// a `:has()` is being matched against, if any
+fn matches(elem: &dom::NodeRef, selector: &css::Selector, scope: Option<&dom::NodeRef>) -> bool {
+  match *selector {
+    css::Selector::Simple(ref simple_selector) => matches_simple_selector(elem, simple_selector, scope),
+    css::Selector::Compound(ref rest, combinator, ref simple_selector) => {
+      if !matches_simple_selector(elem, simple_selector, scope) {
+        return false;
+      }
+      match combinator {
+        css::Combinator::Descendant => elem.ancestor_elements().any(|ancestor| matches(ancestor, rest, scope)),
+        css::Combinator::Child => elem.parent_element().is_some_and(|parent| matches(parent, rest, scope)),
+        css::Combinator::NextSibling => {
+          elem.previous_element_siblings().next().is_some_and(|sibling| matches(&sibling, rest, scope))
+        }
+        css::Combinator::SubsequentSibling => {
+          elem.previous_element_siblings().any(|sibling| matches(&sibling, rest, scope))
+        }
+      }
+    }
+    css::Selector::Scope => scope.is_some_and(|scope| std::ptr::eq(scope.node, elem.node)),
+  }
+}
+
+fn matches_any(elem: &dom::NodeRef, selectors: &[css::Selector], scope: Option<&dom::NodeRef>) -> bool {
+  selectors.iter().any(|selector| matches(elem, selector, scope))
+}
+
+// does `elem` match the given `css::SimpleSelector`?
+fn matches_simple_selector(elem_ref: &dom::NodeRef, selector: &css::SimpleSelector,
+                           scope: Option<&dom::NodeRef>) -> bool {
+  let elem = match elem_ref.element() {
+    Some(elem) => elem,
+    None => return false,
+  };
+
+  // type
+  if selector.tag_name.iter().any(|name| elem.tag_name != *name) {
+    return false;
+  }
+
+  // check id
+  if selector.id.iter().any(|id| elem.id() != Some(id)) {
+    return false;
+  }
+
+  // check class
+  let elem_classes = elem.classes();
+  if selector.class.iter().any(|class| !elem_classes.contains(&class.as_str())) {
+    return false;
+  }
+
+  // check attributes
+  if !selector.attributes.iter().all(|attribute| {
+    elem.get_attribute(&attribute.name).is_some_and(|value| attribute.matches(value))
+  }) {
+    return false;
+  }
+
+  // check pseudo-classes
+  selector.pseudo_classes.iter().all(|pseudo_class| matches_pseudo_class(elem_ref, pseudo_class, scope))
+}
+
+// does `elem` (an element) match `pseudo_class`?
+fn matches_pseudo_class(elem: &dom::NodeRef, pseudo_class: &css::PseudoClass, scope: Option<&dom::NodeRef>) -> bool {
+  use css::PseudoClass;
+
+  let tag_name = elem.element().map(|elem| &elem.tag_name);
+  let same_type = |sibling: &dom::NodeRef| sibling.element().map(|elem| &elem.tag_name) == tag_name;
+  match *pseudo_class {
+    PseudoClass::Root => elem.parent.is_some_and(|parent| matches!(parent.node.node_type, dom::NodeType::Document(_))),
+    PseudoClass::Empty => elem.node.children.iter().all(|child| matches!(child.node_type, dom::NodeType::Comment(_))),
+    PseudoClass::FirstChild => elem.previous_element_siblings().next().is_none(),
+    PseudoClass::LastChild => elem.next_element_siblings().next().is_none(),
+    PseudoClass::OnlyChild => {
+      elem.previous_element_siblings().next().is_none() && elem.next_element_siblings().next().is_none()
+    }
+    PseudoClass::NthChild(nth, ref of) => {
+      (of.is_empty() || matches_any(elem, of, scope)) && nth.matches(1 + elem.previous_element_siblings()
+        .filter(|sibling| of.is_empty() || matches_any(sibling, of, scope)).count())
+    }
+    PseudoClass::NthLastChild(nth, ref of) => {
+      (of.is_empty() || matches_any(elem, of, scope)) && nth.matches(1 + elem.next_element_siblings()
+        .filter(|sibling| of.is_empty() || matches_any(sibling, of, scope)).count())
+    }
+    PseudoClass::NthOfType(nth) => nth.matches(1 + elem.previous_element_siblings().filter(same_type).count()),
+    PseudoClass::NthLastOfType(nth) => nth.matches(1 + elem.next_element_siblings().filter(same_type).count()),
+    PseudoClass::Not(ref selectors) => !matches_any(elem, selectors, scope),
+    PseudoClass::Is(ref selectors) | PseudoClass::Where(ref selectors) => matches_any(elem, selectors, scope),
+    PseudoClass::Has(ref selectors) => selectors.iter().any(|selector| matches_relative(elem, selector)),
+  }
+}
+
+// does any element relative to `elem` match `selector`, which
+// starts at `css::Selector::Scope`? sibling combinators after
+// the scope look among the later siblings and their
+// descendants, others among `elem`'s descendants
+fn matches_relative(elem: &dom::NodeRef, selector: &css::Selector) -> bool {
+  let mut test = |candidate: &dom::NodeRef| matches(candidate, selector, Some(elem));
+  match leading_combinator(selector) {
+    css::Combinator::Descendant | css::Combinator::Child => elem.any_descendant_element(&mut test),
+    css::Combinator::NextSibling | css::Combinator::SubsequentSibling => {
+      elem.next_element_siblings().any(|sibling| test(&sibling) || sibling.any_descendant_element(&mut test))
+    }
+  }
+}
+
+// the combinator right after the scope of a relative selector
+fn leading_combinator(selector: &css::Selector) -> css::Combinator {
+  match *selector {
+    css::Selector::Compound(ref rest, combinator, _) => match **rest {
+      css::Selector::Scope => combinator,
+      _ => leading_combinator(rest),
+    },
+    _ => css::Combinator::Descendant,
+  }
+}
+
+// match a single css::Rule to a dom::Element
+// returns a MatchedRule if there's a match, None otherwise
+fn match_rule<'a> (elem: &dom::NodeRef, rule: &'a css::Rule) -> Option<MatchedRule<'a>> {
+  rule.selectors.iter().find(|selector| matches(elem, selector, None))
+    .map(|selector| (selector.specificity(), rule))
+}
+
+// return rules that match the given element
+fn matching_rules<'a> (elem: &dom::NodeRef, stylesheet: &'a css::Stylesheet) -> Vec<MatchedRule<'a>> {
+  stylesheet.rules.iter().filter_map(|rule| match_rule(elem, rule)).collect()
+}
+
+// how a declaration from `origin` ranks in the cascade:
+// `!important` reverses the order of the origins
+fn precedence(origin: Origin, important: bool) -> u8 {
+  match (important, origin) {
+    (false, Origin::UserAgent) => 0,
+    (false, Origin::User) => 1,
+    (false, Origin::Author) => 2,
+    (true, Origin::Author) => 3,
+    (true, Origin::User) => 4,
+    (true, Origin::UserAgent) => 5,
+  }
+}
+
+// apply styles to an element, returning the cascaded values
+// (shorthands are expanded into their longhands)
+fn cascaded_values(elem: &dom::NodeRef, stylesheets: &[OriginSheet]) -> PropertyMap {
+  let mut values = HashMap::new();
+
+  // matching declarations in source order
+  let mut declarations = Vec::new();
+  for &(origin, stylesheet) in stylesheets.iter() {
+    for (specificity, rule) in matching_rules(elem, stylesheet) {
+      for declaration in rule.declarations.iter() {
+        declarations.push((precedence(origin, declaration.important), specificity, declaration));
+      }
+    }
+  }
+
+  // go through them in cascade order; the sort is stable,
+  // so later declarations still win ties
+  declarations.sort_by_key(|&(precedence, specificity, _)| (precedence, specificity));
+
+  for &(_, _, declaration) in declarations.iter() {
+    let longhands = properties::longhands(&declaration.name);
+    if longhands.is_empty() {
+      values.insert(declaration.name.clone(), declaration.value.clone());
+    }
+    for (index, longhand) in longhands.iter().enumerate() {
+      values.insert(longhand.to_string(), properties::longhand_value(&declaration.value, index));
+    }
+  }
+
+  values
+}
+
+// the built-in user agent stylesheet, parsed on first use
+pub fn user_agent_stylesheet() -> &'static css::Stylesheet {
+  static STYLESHEET: OnceLock<css::Stylesheet> = OnceLock::new();
+  STYLESHEET.get_or_init(|| css::parse(include_str!("ua.css").to_string()))
+}
+
+// create and return style tree, cascading the user agent
+// stylesheet and then `stylesheets` (given in source order,
+// each with its origin)
+pub fn style_tree<'a>(root: &'a dom::Node, stylesheets: &[OriginSheet]) -> StyledNode<'a> {
+  let mut all_sheets = vec![(Origin::UserAgent, user_agent_stylesheet())];
+  all_sheets.extend_from_slice(stylesheets);
+  style_node(root, dom::NodeRef::root(root), None, None, &all_sheets)
+}
+
+// style `node` (found at `node_ref`), given its parent's specified
+// and computed values and the root element's font size (unknown
+// above the root)
+fn style_node<'a>(node: &'a dom::Node, node_ref: dom::NodeRef, parent: Option<(&PropertyMap, &ComputedStyle)>,
+                  root_font_size: Option<f32>, stylesheets: &[OriginSheet]) -> StyledNode<'a> {
+  let cascaded = match node.node_type {
+    dom::NodeType::Element(_) => cascaded_values(&node_ref, stylesheets),
+    _ => HashMap::new()
+  };
+  let values = specified_values(cascaded, parent);
+
+  let parent_computed = parent.map(|(_, computed)| computed);
+  let mut computed = ComputedStyle::compute(&values, parent_computed,
+                                            root_font_size.unwrap_or(computed::MEDIUM_FONT_SIZE));
+
+  // the document itself is the root block,
+  // and comments and doctypes are never rendered
+  match node.node_type {
+    dom::NodeType::Document(_) => computed.display = layout::Display::Block,
+    dom::NodeType::Comment(_) | dom::NodeType::Doctype(_) => computed.display = layout::Display::None,
+    _ => {}
+  }
+
+  // the root element sets the font size for `rem`
+  let root_font_size = match node.node_type {
+    dom::NodeType::Document(_) => None,
+    _ => root_font_size.or(Some(computed.font_size)),
+  };
+
+  StyledNode {
+    node,
+    children: node.children.iter().enumerate().map(|(index, child)| {
+      let child_ref = dom::NodeRef { node: child, parent: Some(&node_ref), index };
+      style_node(child, child_ref, Some((&values, &computed)), root_font_size, stylesheets)
+    }).collect(),
+    specified_values: values,
+    computed,
+  }
+}
+
+// fill in every known property a node's cascade didn't set,
+// inheriting from `parent` or using the initial value (which
+// is also what `inherit`, `initial` and `unset` ask for)
+fn specified_values(mut cascaded: PropertyMap, parent: Option<(&PropertyMap, &ComputedStyle)>) -> PropertyMap {
+  let initial_values = properties::initial_values();
+
+  for property in properties::PROPERTIES.iter() {
+    let keyword = match cascaded.get(property.name) {
+      Some(css::Value::Keyword(keyword)) if matches!(keyword.as_str(), "inherit" | "initial" | "unset") => {
+        Some(keyword.clone())
+      }
+      Some(_) => continue,
+      None => None,
+    };
+
+    let inherit = match keyword.as_deref() {
+      Some("inherit") => true,
+      Some("initial") => false,
+      _ => property.inherited,
+    };
+    let value = match parent {
+      Some((values, computed)) if inherit => computed.inherited_value(property.name, &values[property.name]),
+      _ => initial_values[property.name].clone(),
+    };
+    cascaded.insert(property.name.to_string(), value);
+  }
+
+  cascaded
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{style_tree, user_agent_stylesheet, Origin, StyledNode};
+  use crate::layout;
+  use crate::css;
+  use crate::html;
+
+  // the styled <body> element (html::parse always adds one)
+  fn body<'a>(root: &'a StyledNode<'a>) -> &'a StyledNode<'a> {
+    &root.children[0].children[1]
+  }
+
+  #[test]
+  fn matches_tag_class_and_id() {
+    let sheet = css::parse("p { width: 1px; } .a { height: 2px; } #b { margin: 3px; }".to_string());
+    let dom = html::parse("<p class=\"a\" id=\"b\"></p><p></p>".to_string());
+    let styled = style_tree(&dom, &[(Origin::Author, &sheet)]);
+
+    let first = &body(&styled).children[0];
+    assert_eq!(first.value("width"), Some(css::Value::Length(1.0, css::Unit::Px)));
+    assert_eq!(first.value("height"), Some(css::Value::Length(2.0, css::Unit::Px)));
+    assert_eq!(first.value("margin-top"), Some(css::Value::Length(3.0, css::Unit::Px)));
+
+    let second = &body(&styled).children[1];
+    assert_eq!(second.value("width"), Some(css::Value::Length(1.0, css::Unit::Px)));
+    assert_eq!(second.value("height"), Some(css::Value::Keyword("auto".to_string())));
+  }
+
+  #[test]
+  fn more_specific_rules_win() {
+    let sheet = css::parse("#x { color: #0000ff; } .y { color: #00ff00; } p { color: #ff0000; }".to_string());
+    let dom = html::parse("<p id=\"x\" class=\"y\"></p><p class=\"y\"></p>".to_string());
+    let styled = style_tree(&dom, &[(Origin::Author, &sheet)]);
+
+    assert_eq!(format!("{}", body(&styled).children[0].value("color").unwrap()), "#0000ff");
+    assert_eq!(format!("{}", body(&styled).children[1].value("color").unwrap()), "#00ff00");
+  }
+
+  #[test]
+  fn unknown_units_fall_back_to_earlier_declarations() {
+    let sheet = css::parse("p { width: 100px; } p { width: 10vw; height: 2q; }".to_string());
+    let dom = html::parse("<p></p>".to_string());
+    let styled = style_tree(&dom, &[(Origin::Author, &sheet)]);
+    let p = &body(&styled).children[0];
+
+    assert_eq!(p.computed.width, crate::computed::Length::Px(100.0));
+    assert_eq!(p.computed.height, crate::computed::Length::Auto);
+  }
+
+  #[test]
+  fn lookup_falls_back() {
+    let sheet = css::parse("p { margin: 4px; margin-left: 8px; }".to_string());
+    let dom = html::parse("<p></p>".to_string());
+    let styled = style_tree(&dom, &[(Origin::Author, &sheet)]);
+    let p = &body(&styled).children[0];
+    let zero = css::Value::Length(0.0, css::Unit::Px);
+
+    assert_eq!(p.lookup("margin-left", "margin", &zero), css::Value::Length(8.0, css::Unit::Px));
+    assert_eq!(p.lookup("margin-top", "margin", &zero), css::Value::Length(4.0, css::Unit::Px));
+    assert_eq!(p.lookup("padding-top", "padding", &zero), zero);
+  }
+
+  #[test]
+  fn cascades_by_origin_importance_specificity_and_order() {
+    let ua = css::parse("p { width: 1px !important; height: 1px; margin: 1px; } #x { padding: 1px; }".to_string());
+    let user = css::parse("p { height: 2px !important; margin: 2px; }".to_string());
+    let author = css::parse("p { width: 3px; height: 3px !important; margin: 3px; padding: 3px; }".to_string());
+    let later = css::parse("p { margin: 4px; } p { padding: 4px; }".to_string());
+    let dom = html::parse("<p id=\"x\"></p>".to_string());
+    let styled = style_tree(&dom, &[(Origin::UserAgent, &ua), (Origin::Author, &author),
+                                    (Origin::User, &user), (Origin::Author, &later)]);
+    let p = &body(&styled).children[0];
+    let px = |n| Some(css::Value::Length(n, css::Unit::Px));
+
+    // important user agent declarations beat everything
+    assert_eq!(p.value("width"), px(1.0));
+    // important user declarations beat important author ones
+    assert_eq!(p.value("height"), px(2.0));
+    // normal author declarations beat user ones, and later ones win
+    assert_eq!(p.value("margin-top"), px(4.0));
+    // origin matters more than specificity
+    assert_eq!(p.value("padding-left"), px(4.0));
+  }
+
+  #[test]
+  fn user_agent_styles_come_first() {
+    // the whole sheet parses
+    let (_, warnings) = css::parse_with_warnings(include_str!("ua.css").to_string());
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    assert!(!user_agent_stylesheet().rules.is_empty());
+
+    let sheet = css::parse("h1 { margin-top: 0px; } span { display: block; }".to_string());
+    let dom = html::parse("<h1>a</h1><span></span><li></li><script></script>".to_string());
+    let styled = style_tree(&dom, &[(Origin::Author, &sheet)]);
+    let body = body(&styled);
+
+    assert_eq!(body.value("margin-left"), Some(css::Value::Length(8.0, css::Unit::Px)));
+    assert_eq!(body.children[0].computed.font_size, 32.0);
+    assert_eq!(body.children[0].value("margin-top"), Some(css::Value::Length(0.0, css::Unit::Px)));
+    let displays: Vec<layout::Display> = body.children.iter().map(|child| child.display()).collect();
+    assert_eq!(displays, vec![layout::Display::Block, layout::Display::Block,
+                              layout::Display::Block, layout::Display::None]);
+    assert_eq!(styled.children[0].children[0].display(), layout::Display::None);
+  }
+
+  #[test]
+  fn inherits_and_fills_in_initial_values() {
+    let sheet = css::parse("body { color: #ff0000; font-size: 20px; padding: 2px; }
+                            em { color: initial; padding: inherit; } span { font-size: 10px; }".to_string());
+    let dom = html::parse("<p>a<em>b</em><span>c</span></p>".to_string());
+    let styled = style_tree(&dom, &[(Origin::Author, &sheet)]);
+    let p = &body(&styled).children[0];
+    let px = |n| Some(css::Value::Length(n, css::Unit::Px));
+    let red = Some(css::Value::ColorValue(css::Color { r: 255, g: 0, b: 0, a: 255 }));
+
+    // inherited properties reach text nodes; others don't
+    let text = &p.children[0];
+    assert_eq!(text.value("color"), red);
+    assert_eq!(text.value("font-size"), px(20.0));
+    assert_eq!(text.value("padding-left"), px(0.0));
+    assert_eq!(text.value("display"), Some(css::Value::Keyword("inline".to_string())));
+
+    let em = &p.children[1];
+    assert_eq!(em.value("color"), Some(css::Value::ColorValue(css::Color { r: 0, g: 0, b: 0, a: 255 })));
+    assert_eq!(em.value("padding-top"), px(0.0));
+    assert_eq!(em.children[0].value("color"), em.value("color"));
+    assert_eq!(p.children[2].children[0].value("font-size"), px(10.0));
+
+    // the document itself has the initial values
+    assert_eq!(styled.value("font-size"), Some(css::Value::Keyword("medium".to_string())));
+  }
+
+  #[test]
+  fn computes_relative_values_down_the_tree() {
+    let sheet = css::parse("html { font-size: 10px; } div { font-size: 2em; margin-top: 1em; width: 2rem; }
+                            p { color: currentcolor; }".to_string());
+    let dom = html::parse("<div><div>a</div></div><h1><b>b</b></h1><p>c</p>".to_string());
+    let styled = style_tree(&dom, &[(Origin::Author, &sheet)]);
+    let body = body(&styled);
+
+    // ems compound through nested elements, but not into text
+    let outer = &body.children[0];
+    let inner = &outer.children[0];
+    assert_eq!((outer.computed.font_size, inner.computed.font_size), (20.0, 40.0));
+    assert_eq!(inner.children[0].computed.font_size, 40.0);
+    assert_eq!(inner.computed.margin.top, crate::computed::Length::Px(40.0));
+    assert_eq!(inner.computed.width, crate::computed::Length::Px(20.0));
+
+    // the user agent's headings and bolder
+    let b = &body.children[1].children[0];
+    assert_eq!((b.computed.font_size, b.computed.font_weight), (20.0, 900));
+    assert_eq!(body.children[2].children[0].computed.color, css::Color { r: 0, g: 0, b: 0, a: 255 });
+  }
+
+  #[test]
+  fn matches_combinators() {
+    let sheet = css::parse("ul a { width: 1px; } ul > a { height: 2px; } h1 + p { padding: 3px; }
+                            h1 ~ p { margin: 4px; } div > * > i { width: 5px; }".to_string());
+    let dom = html::parse("<ul><li><a>x</a></li><a></a></ul><h1></h1><p></p>
+                           <!-- c --><p>y</p><div><em><i></i></em><i></i></div>".to_string());
+    let styled = style_tree(&dom, &[(Origin::Author, &sheet)]);
+    let body = body(&styled);
+    let px = |n| Some(css::Value::Length(n, css::Unit::Px));
+    let auto = Some(css::Value::Keyword("auto".to_string()));
+
+    // descendants at any depth, children only one level down
+    let ul = &body.children[0];
+    let nested = &ul.children[0].children[0];
+    assert_eq!((nested.value("width"), nested.value("height")), (px(1.0), auto.clone()));
+    assert_eq!((ul.children[1].value("width"), ul.children[1].value("height")), (px(1.0), px(2.0)));
+
+    // siblings skip text and comments, but only the next one is adjacent
+    let first = &body.children[2];
+    let second = &body.children[5];
+    assert_eq!((first.value("padding-top"), first.value("margin-top")), (px(3.0), px(4.0)));
+    assert_eq!((second.value("padding-top"), second.value("margin-top")), (px(0.0), px(4.0)));
+
+    // `*` in the middle of a chain
+    let div = &body.children[6];
+    assert_eq!(div.children[0].children[0].value("width"), px(5.0));
+    assert_eq!(div.children[1].value("width"), auto);
+  }
+
+  #[test]
+  fn matches_attributes() {
+    let sheet = css::parse("input[type=text] { width: 1px; } [data-wide] { width: 2px; }
+                            a[href$=\".pdf\" i] { height: 3px; }".to_string());
+    let dom = html::parse("<input type=text><input type=checkbox data-wide>
+                           <a href=\"a.PDF\"></a><a href=\"a.pdf.html\"></a><p hidden></p>".to_string());
+    let styled = style_tree(&dom, &[(Origin::Author, &sheet)]);
+    let body = body(&styled);
+    let px = |n| Some(css::Value::Length(n, css::Unit::Px));
+
+    assert_eq!(body.children[0].value("width"), px(1.0));
+    assert_eq!(body.children[1].value("width"), px(2.0));
+    assert_eq!(body.children[3].value("height"), px(3.0));
+    assert_eq!(body.children[4].value("height"), Some(css::Value::Keyword("auto".to_string())));
+    // the user agent hides `hidden` elements
+    assert_eq!(body.children[5].display(), layout::Display::None);
+  }
+
+  #[test]
+  fn matches_structural_pseudo_classes() {
+    let sheet = css::parse("li:first-child { width: 1px; } li:last-child { width: 2px; }
+                            li:nth-child(2n of .x) { height: 3px; } i:only-child { width: 4px; }
+                            b:nth-of-type(2) { width: 5px; } b:nth-last-child(1) { height: 6px; }
+                            :root { width: 7px; } div:empty { height: 8px; }".to_string());
+    let dom = html::parse("<ul><li></li><li class=x></li><li></li><li class=x></li></ul>
+                           <p><i></i></p><p><b></b><i></i><b></b></p><div><!-- c --></div><div> </div>".to_string());
+    let styled = style_tree(&dom, &[(Origin::Author, &sheet)]);
+    let body = body(&styled);
+    let px = |n| Some(css::Value::Length(n, css::Unit::Px));
+    let auto = Some(css::Value::Keyword("auto".to_string()));
+
+    let li: Vec<(Option<css::Value>, Option<css::Value>)> = body.children[0].children.iter()
+      .map(|li| (li.value("width"), li.value("height"))).collect();
+    assert_eq!(li, vec![(px(1.0), auto.clone()), (auto.clone(), auto.clone()),
+                        (auto.clone(), auto.clone()), (px(2.0), px(3.0))]);
+
+    assert_eq!(body.children[2].children[0].value("width"), px(4.0));
+    let p = &body.children[3];
+    assert_eq!(p.children[1].value("width"), auto);
+    assert_eq!((p.children[2].value("width"), p.children[2].value("height")), (px(5.0), px(6.0)));
+    assert_eq!(styled.children[0].value("width"), px(7.0));
+    assert_eq!(body.children[4].value("height"), px(8.0));
+    assert_eq!(body.children[5].value("height"), auto);
+  }
+
+  #[test]
+  fn matches_logical_pseudo_classes() {
+    let sheet = css::parse("p:not(.a, .b) { width: 1px; } :is(h1, h2) > span { width: 2px; }
+                            div:has(> img) { width: 3px; } div:has(em i) { height: 4px; }
+                            h1:has(+ p) { height: 5px; } h2:has(~ div img) { height: 6px; }
+                            #z { padding: 7px; } :where(#z) { padding: 8px; } :is(#z) { padding: 9px; }".to_string());
+    let dom = html::parse("<p class=a></p><p id=z></p><h1><span></span></h1><p></p>
+                           <h2><span></span></h2><div><img></div><div><em><i></i></em></div>".to_string());
+    let styled = style_tree(&dom, &[(Origin::Author, &sheet)]);
+    let body = body(&styled);
+    let px = |n| Some(css::Value::Length(n, css::Unit::Px));
+    let auto = Some(css::Value::Keyword("auto".to_string()));
+
+    assert_eq!((body.children[0].value("width"), body.children[1].value("width")), (auto.clone(), px(1.0)));
+    assert_eq!(body.children[2].children[0].value("width"), px(2.0));
+    assert_eq!(body.children[5].children[0].value("width"), px(2.0));
+    assert_eq!((body.children[6].value("width"), body.children[6].value("height")), (px(3.0), auto.clone()));
+    assert_eq!((body.children[7].value("width"), body.children[7].value("height")), (auto, px(4.0)));
+    assert_eq!(body.children[2].value("height"), px(5.0));
+    assert_eq!(body.children[5].value("height"), px(6.0));
+
+    // `:where()` adds nothing to the specificity, so `#z` wins
+    // over it; `:is(#z)` ties with `#z` and comes later
+    assert_eq!(body.children[1].value("padding-top"), px(9.0));
+  }
+}
@@ -0,0 +1,1096 @@
+//! HTML tokenizer, following the states of the WHATWG
+//! tokenization algorithm
+//! (https://html.spec.whatwg.org/multipage/parsing.html#tokenization)
+//!
+//! the tree builder in `html` pulls tokens with `next_token`
+//! and switches the tokenizer into the raw text states when
+//! it opens an element whose contents aren't markup
+
+use std::collections::VecDeque;
+use crate::entities;
+use crate::parser;
+use crate::parser::ParseError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+  Doctype(Doctype),
+  StartTag(Tag),
+  EndTag(Tag),
+  Comment(String),
+  Character(char),
+  Eof,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Tag {
+  pub name: String,
+  pub self_closing: bool,
+  pub attributes: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Doctype {
+  pub name: Option<String>,
+  pub public_id: Option<String>,
+  pub system_id: Option<String>,
+  pub force_quirks: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum State {
+  Data,
+  Rcdata,
+  Rawtext,
+  Plaintext,
+  TagOpen,
+  EndTagOpen,
+  TagName,
+  // `<`, `</` and `</name` inside RCDATA or RAWTEXT, which
+  // is kept in `Tokenizer::text_state`
+  TextLessThanSign,
+  TextEndTagOpen,
+  TextEndTagName,
+  BeforeAttributeName,
+  AttributeName,
+  AfterAttributeName,
+  BeforeAttributeValue,
+  AttributeValueQuoted(char),
+  AttributeValueUnquoted,
+  AfterAttributeValueQuoted,
+  SelfClosingStartTag,
+  BogusComment,
+  CommentStart,
+  CommentStartDash,
+  Comment,
+  CommentEndDash,
+  CommentEnd,
+  CommentEndBang,
+  Doctype,
+  BeforeDoctypeName,
+  DoctypeName,
+  AfterDoctypeName,
+  AfterDoctypeKeyword(DoctypeId),
+  BeforeDoctypeIdentifier(DoctypeId),
+  DoctypeIdentifier(DoctypeId, char),
+  AfterDoctypePublicIdentifier,
+  BetweenDoctypePublicAndSystemIdentifiers,
+  AfterDoctypeSystemIdentifier,
+  BogusDoctype,
+}
+
+// which of a doctype's identifiers is being parsed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DoctypeId {
+  Public,
+  System,
+}
+
+pub struct Tokenizer {
+  p: parser::Parser,
+  state: State,
+  // the state to go back to from the `Text*` states
+  text_state: State,
+  pending: VecDeque<Token>,
+  tag: Tag,
+  is_end_tag: bool,
+  // where each of the tag's attributes starts
+  attribute_starts: Vec<usize>,
+  comment: String,
+  doctype: Doctype,
+  temp_buffer: String,
+  last_start_tag: Option<String>,
+  // where the tag (or comment or doctype) being tokenized starts
+  pub token_start: usize,
+  pub warnings: Vec<ParseError>,
+}
+
+impl Tokenizer {
+  pub fn new(source: String) -> Tokenizer {
+    // newlines are normalized before tokenizing
+    let input = source.replace("\r\n", "\n").replace('\r', "\n");
+    Tokenizer {
+      p: parser::Parser::new(input),
+      state: State::Data,
+      text_state: State::Data,
+      pending: VecDeque::new(),
+      tag: Tag::default(),
+      is_end_tag: false,
+      attribute_starts: Vec::new(),
+      comment: String::new(),
+      doctype: Doctype::default(),
+      temp_buffer: String::new(),
+      last_start_tag: None,
+      token_start: 0,
+      warnings: Vec::new(),
+    }
+  }
+
+  // switch state, e.g. to RAWTEXT after a <style> start tag
+  pub fn switch_to(&mut self, state: State) {
+    if matches!(state, State::Rcdata | State::Rawtext) {
+      self.text_state = state;
+    }
+    self.state = state;
+  }
+
+  // the current position in the (normalized) input
+  pub fn position(&self) -> usize {
+    self.p.pos
+  }
+
+  // record a parse error at `byte_offset`
+  pub fn warn_at(&mut self, byte_offset: usize, expected: &str, found: &str) {
+    let warning = self.p.error_at(byte_offset, expected, found);
+    self.warnings.push(warning);
+  }
+
+  // return the next token, running the state machine until
+  // it emits one
+  pub fn next_token(&mut self) -> Token {
+    while self.pending.is_empty() {
+      self.step();
+    }
+    self.pending.pop_front().unwrap()
+  }
+
+  fn consume(&mut self) -> Option<char> {
+    if self.p.eof() {
+      None
+    } else {
+      Some(self.p.consume_char())
+    }
+  }
+
+  // put `c` back, to be consumed again in the next state
+  fn reconsume(&mut self, c: Option<char>) {
+    if let Some(c) = c {
+      self.p.pos -= c.len_utf8();
+    }
+  }
+
+  // record a parse error about `c`, which was just consumed
+  fn warn(&mut self, expected: &str, c: Option<char>) {
+    match c {
+      Some(c) => {
+        let pos = self.p.pos - c.len_utf8();
+        self.warn_at(pos, expected, &format!("`{}`", c));
+      }
+      None => {
+        let pos = self.p.pos;
+        self.warn_at(pos, expected, "end of input");
+      }
+    }
+  }
+
+  // consume `s` if the input continues with it, ignoring ASCII case
+  fn consume_keyword(&mut self, s: &str) -> bool {
+    let rest = &self.p.input[self.p.pos..];
+    if rest.len() >= s.len() && rest.is_char_boundary(s.len()) && rest[..s.len()].eq_ignore_ascii_case(s) {
+      self.p.pos += s.len();
+      true
+    } else {
+      false
+    }
+  }
+
+  fn emit(&mut self, token: Token) {
+    self.pending.push_back(token);
+  }
+
+  fn emit_str(&mut self, s: &str) {
+    for c in s.chars() {
+      self.emit(Token::Character(c));
+    }
+  }
+
+  fn emit_eof(&mut self) {
+    self.state = State::Data;
+    self.emit(Token::Eof);
+  }
+
+  fn new_tag(&mut self, is_end_tag: bool) {
+    self.tag = Tag::default();
+    self.is_end_tag = is_end_tag;
+    self.attribute_starts.clear();
+  }
+
+  fn start_attribute(&mut self, start: usize, name: &str) {
+    self.tag.attributes.push((name.to_string(), String::new()));
+    self.attribute_starts.push(start);
+  }
+
+  fn emit_tag(&mut self) {
+    self.state = State::Data;
+    let mut tag = std::mem::take(&mut self.tag);
+
+    // the first of several attributes with the same name wins
+    let attributes = std::mem::take(&mut tag.attributes);
+    for (attribute, &start) in attributes.into_iter().zip(self.attribute_starts.iter()) {
+      if tag.attributes.iter().any(|(name, _)| *name == attribute.0) {
+        let warning = self.p.error_at(start, "a unique attribute name", &format!("`{}`", attribute.0));
+        self.warnings.push(warning);
+      } else {
+        tag.attributes.push(attribute);
+      }
+    }
+
+    if self.is_end_tag {
+      if !tag.attributes.is_empty() || tag.self_closing {
+        let start = self.token_start;
+        self.warn_at(start, "an end tag without attributes", &format!("`</{}`", tag.name));
+      }
+      self.emit(Token::EndTag(tag));
+    } else {
+      self.last_start_tag = Some(tag.name.clone());
+      self.emit(Token::StartTag(tag));
+    }
+  }
+
+  fn emit_comment(&mut self) {
+    self.state = State::Data;
+    let comment = std::mem::take(&mut self.comment);
+    self.emit(Token::Comment(comment));
+  }
+
+  fn emit_doctype(&mut self) {
+    self.state = State::Data;
+    let doctype = std::mem::take(&mut self.doctype);
+    self.emit(Token::Doctype(doctype));
+  }
+
+  // emit the doctype at EOF, which always means quirks mode
+  fn emit_doctype_at_eof(&mut self) {
+    self.warn("`>`", None);
+    self.doctype.force_quirks = true;
+    self.emit_doctype();
+    self.emit_eof();
+  }
+
+  // is the end tag being tokenized the end tag for the last
+  // start tag, which is what ends RCDATA and RAWTEXT?
+  fn appropriate_end_tag(&self) -> bool {
+    self.last_start_tag.as_deref() == Some(self.tag.name.as_str())
+  }
+
+  // give up on an end tag in RCDATA or RAWTEXT, and emit
+  // what was consumed of it as text
+  fn abandon_text_end_tag(&mut self, c: Option<char>) {
+    let text = format!("</{}", self.temp_buffer);
+    self.emit_str(&text);
+    self.reconsume(c);
+    self.state = self.text_state;
+  }
+
+  fn attribute_name(&mut self) -> &mut String {
+    &mut self.tag.attributes.last_mut().unwrap().0
+  }
+
+  fn attribute_value(&mut self) -> &mut String {
+    &mut self.tag.attributes.last_mut().unwrap().1
+  }
+
+  fn doctype_id(&mut self, id: DoctypeId) -> &mut Option<String> {
+    match id {
+      DoctypeId::Public => &mut self.doctype.public_id,
+      DoctypeId::System => &mut self.doctype.system_id,
+    }
+  }
+
+  // consume one char (or none, at EOF) and act on it
+  // according to the current state
+  fn step(&mut self) {
+    let c = self.consume();
+    match self.state {
+      State::Data => match c {
+        Some('<') => {
+          self.token_start = self.p.pos - 1;
+          self.state = State::TagOpen;
+        }
+        Some('&') => {
+          let text = self.consume_character_reference(false);
+          self.emit_str(&text);
+        }
+        Some(c) => self.emit(Token::Character(c)),
+        None => self.emit_eof(),
+      },
+      State::Rcdata => match c {
+        Some('<') => {
+          self.token_start = self.p.pos - 1;
+          self.state = State::TextLessThanSign;
+        }
+        Some('&') => {
+          let text = self.consume_character_reference(false);
+          self.emit_str(&text);
+        }
+        Some(c) => self.emit(Token::Character(c)),
+        None => self.emit_eof(),
+      },
+      State::Rawtext => match c {
+        Some('<') => {
+          self.token_start = self.p.pos - 1;
+          self.state = State::TextLessThanSign;
+        }
+        Some(c) => self.emit(Token::Character(c)),
+        None => self.emit_eof(),
+      },
+      State::Plaintext => match c {
+        Some(c) => self.emit(Token::Character(c)),
+        None => self.emit_eof(),
+      },
+      State::TagOpen => match c {
+        Some('!') => {
+          // markup declaration open
+          self.comment.clear();
+          if self.consume_keyword("--") {
+            self.state = State::CommentStart;
+          } else if self.consume_keyword("DOCTYPE") {
+            self.doctype = Doctype::default();
+            self.state = State::Doctype;
+          } else {
+            // including CDATA sections, which are only
+            // allowed in foreign content
+            self.warn("`<!--` or `<!DOCTYPE`", c);
+            self.state = State::BogusComment;
+          }
+        }
+        Some('/') => self.state = State::EndTagOpen,
+        Some(ch) if ch.is_ascii_alphabetic() => {
+          self.new_tag(false);
+          self.reconsume(c);
+          self.state = State::TagName;
+        }
+        Some('?') => {
+          self.warn("a tag name", c);
+          self.comment.clear();
+          self.reconsume(c);
+          self.state = State::BogusComment;
+        }
+        None => {
+          self.warn("a tag name", c);
+          self.emit(Token::Character('<'));
+          self.emit_eof();
+        }
+        Some(_) => {
+          self.warn("a tag name", c);
+          self.emit(Token::Character('<'));
+          self.reconsume(c);
+          self.state = State::Data;
+        }
+      },
+      State::EndTagOpen => match c {
+        Some(ch) if ch.is_ascii_alphabetic() => {
+          self.new_tag(true);
+          self.reconsume(c);
+          self.state = State::TagName;
+        }
+        Some('>') => {
+          self.warn("a tag name", c);
+          self.state = State::Data;
+        }
+        None => {
+          self.warn("a tag name", c);
+          self.emit_str("</");
+          self.emit_eof();
+        }
+        Some(_) => {
+          self.warn("a tag name", c);
+          self.comment.clear();
+          self.reconsume(c);
+          self.state = State::BogusComment;
+        }
+      },
+      State::TagName => match c {
+        Some(ch) if is_space(ch) => self.state = State::BeforeAttributeName,
+        Some('/') => self.state = State::SelfClosingStartTag,
+        Some('>') => self.emit_tag(),
+        Some(ch) => self.tag.name.push(ch.to_ascii_lowercase()),
+        None => {
+          self.warn("`>`", c);
+          self.emit_eof();
+        }
+      },
+      State::TextLessThanSign => match c {
+        Some('/') => {
+          self.temp_buffer.clear();
+          self.state = State::TextEndTagOpen;
+        }
+        _ => {
+          self.emit(Token::Character('<'));
+          self.reconsume(c);
+          self.state = self.text_state;
+        }
+      },
+      State::TextEndTagOpen => match c {
+        Some(ch) if ch.is_ascii_alphabetic() => {
+          self.new_tag(true);
+          self.reconsume(c);
+          self.state = State::TextEndTagName;
+        }
+        _ => self.abandon_text_end_tag(c),
+      },
+      State::TextEndTagName => match c {
+        Some(ch) if is_space(ch) && self.appropriate_end_tag() => {
+          self.state = State::BeforeAttributeName;
+        }
+        Some('/') if self.appropriate_end_tag() => self.state = State::SelfClosingStartTag,
+        Some('>') if self.appropriate_end_tag() => self.emit_tag(),
+        Some(ch) if ch.is_ascii_alphabetic() => {
+          self.tag.name.push(ch.to_ascii_lowercase());
+          self.temp_buffer.push(ch);
+        }
+        _ => self.abandon_text_end_tag(c),
+      },
+      State::BeforeAttributeName => match c {
+        Some(ch) if is_space(ch) => {}
+        Some('/') | Some('>') | None => {
+          self.reconsume(c);
+          self.state = State::AfterAttributeName;
+        }
+        Some('=') => {
+          self.warn("an attribute name", c);
+          self.start_attribute(self.p.pos - 1, "=");
+          self.state = State::AttributeName;
+        }
+        Some(_) => {
+          self.reconsume(c);
+          self.start_attribute(self.p.pos, "");
+          self.state = State::AttributeName;
+        }
+      },
+      State::AttributeName => match c {
+        Some(ch) if is_space(ch) => self.state = State::AfterAttributeName,
+        Some('/') | Some('>') | None => {
+          self.reconsume(c);
+          self.state = State::AfterAttributeName;
+        }
+        Some('=') => self.state = State::BeforeAttributeValue,
+        Some(ch) => {
+          if matches!(ch, '"' | '\'' | '<') {
+            self.warn("an attribute name", c);
+          }
+          // (SVG's camel-cased names aren't restored)
+          self.attribute_name().push(ch.to_ascii_lowercase());
+        }
+      },
+      State::AfterAttributeName => match c {
+        Some(ch) if is_space(ch) => {}
+        Some('/') => self.state = State::SelfClosingStartTag,
+        Some('=') => self.state = State::BeforeAttributeValue,
+        Some('>') => self.emit_tag(),
+        None => {
+          self.warn("`>`", c);
+          self.emit_eof();
+        }
+        Some(_) => {
+          self.reconsume(c);
+          self.start_attribute(self.p.pos, "");
+          self.state = State::AttributeName;
+        }
+      },
+      State::BeforeAttributeValue => match c {
+        Some(ch) if is_space(ch) => {}
+        Some(q) if q == '"' || q == '\'' => self.state = State::AttributeValueQuoted(q),
+        Some('>') => {
+          self.warn("an attribute value", c);
+          self.emit_tag();
+        }
+        _ => {
+          self.reconsume(c);
+          self.state = State::AttributeValueUnquoted;
+        }
+      },
+      State::AttributeValueQuoted(quote) => match c {
+        Some(ch) if ch == quote => self.state = State::AfterAttributeValueQuoted,
+        Some('&') => {
+          let text = self.consume_character_reference(true);
+          self.attribute_value().push_str(&text);
+        }
+        Some(ch) => self.attribute_value().push(ch),
+        None => {
+          self.warn(&format!("`{}`", quote), c);
+          self.emit_eof();
+        }
+      },
+      State::AttributeValueUnquoted => match c {
+        Some(ch) if is_space(ch) => self.state = State::BeforeAttributeName,
+        Some('>') => self.emit_tag(),
+        Some('&') => {
+          let text = self.consume_character_reference(true);
+          self.attribute_value().push_str(&text);
+        }
+        Some(ch) => {
+          if matches!(ch, '"' | '\'' | '<' | '=' | '`') {
+            self.warn("an unquoted attribute value", c);
+          }
+          self.attribute_value().push(ch);
+        }
+        None => {
+          self.warn("`>`", c);
+          self.emit_eof();
+        }
+      },
+      State::AfterAttributeValueQuoted => match c {
+        Some(ch) if is_space(ch) => self.state = State::BeforeAttributeName,
+        Some('/') => self.state = State::SelfClosingStartTag,
+        Some('>') => self.emit_tag(),
+        None => {
+          self.warn("`>`", c);
+          self.emit_eof();
+        }
+        Some(_) => {
+          self.warn("whitespace between attributes", c);
+          self.reconsume(c);
+          self.state = State::BeforeAttributeName;
+        }
+      },
+      State::SelfClosingStartTag => match c {
+        Some('>') => {
+          self.tag.self_closing = true;
+          self.emit_tag();
+        }
+        None => {
+          self.warn("`>`", c);
+          self.emit_eof();
+        }
+        Some(_) => {
+          self.warn("`>`", c);
+          self.reconsume(c);
+          self.state = State::BeforeAttributeName;
+        }
+      },
+      State::BogusComment => match c {
+        Some('>') => self.emit_comment(),
+        Some(ch) => self.comment.push(ch),
+        None => {
+          self.emit_comment();
+          self.emit_eof();
+        }
+      },
+      State::CommentStart => match c {
+        Some('-') => self.state = State::CommentStartDash,
+        Some('>') => {
+          self.warn("comment text", c);
+          self.emit_comment();
+        }
+        _ => {
+          self.reconsume(c);
+          self.state = State::Comment;
+        }
+      },
+      State::CommentStartDash => match c {
+        Some('-') => self.state = State::CommentEnd,
+        Some('>') => {
+          self.warn("comment text", c);
+          self.emit_comment();
+        }
+        None => {
+          self.warn("`-->`", c);
+          self.emit_comment();
+          self.emit_eof();
+        }
+        Some(_) => {
+          self.comment.push('-');
+          self.reconsume(c);
+          self.state = State::Comment;
+        }
+      },
+      State::Comment => match c {
+        Some('-') => self.state = State::CommentEndDash,
+        Some(ch) => self.comment.push(ch),
+        None => {
+          self.warn("`-->`", c);
+          self.emit_comment();
+          self.emit_eof();
+        }
+      },
+      State::CommentEndDash => match c {
+        Some('-') => self.state = State::CommentEnd,
+        None => {
+          self.warn("`-->`", c);
+          self.emit_comment();
+          self.emit_eof();
+        }
+        Some(_) => {
+          self.comment.push('-');
+          self.reconsume(c);
+          self.state = State::Comment;
+        }
+      },
+      State::CommentEnd => match c {
+        Some('>') => self.emit_comment(),
+        Some('!') => self.state = State::CommentEndBang,
+        Some('-') => self.comment.push('-'),
+        None => {
+          self.warn("`-->`", c);
+          self.emit_comment();
+          self.emit_eof();
+        }
+        Some(_) => {
+          self.comment.push_str("--");
+          self.reconsume(c);
+          self.state = State::Comment;
+        }
+      },
+      State::CommentEndBang => match c {
+        Some('-') => {
+          self.comment.push_str("--!");
+          self.state = State::CommentEndDash;
+        }
+        Some('>') => {
+          self.warn("`-->`", c);
+          self.emit_comment();
+        }
+        None => {
+          self.warn("`-->`", c);
+          self.emit_comment();
+          self.emit_eof();
+        }
+        Some(_) => {
+          self.comment.push_str("--!");
+          self.reconsume(c);
+          self.state = State::Comment;
+        }
+      },
+      State::Doctype => match c {
+        Some(ch) if is_space(ch) => self.state = State::BeforeDoctypeName,
+        None => self.emit_doctype_at_eof(),
+        Some(ch) => {
+          if ch != '>' {
+            self.warn("whitespace before the doctype name", c);
+          }
+          self.reconsume(c);
+          self.state = State::BeforeDoctypeName;
+        }
+      },
+      State::BeforeDoctypeName => match c {
+        Some(ch) if is_space(ch) => {}
+        Some('>') => {
+          self.warn("a doctype name", c);
+          self.doctype.force_quirks = true;
+          self.emit_doctype();
+        }
+        None => self.emit_doctype_at_eof(),
+        Some(ch) => {
+          self.doctype.name = Some(ch.to_ascii_lowercase().to_string());
+          self.state = State::DoctypeName;
+        }
+      },
+      State::DoctypeName => match c {
+        Some(ch) if is_space(ch) => self.state = State::AfterDoctypeName,
+        Some('>') => self.emit_doctype(),
+        None => self.emit_doctype_at_eof(),
+        Some(ch) => {
+          if let Some(ref mut name) = self.doctype.name {
+            name.push(ch.to_ascii_lowercase());
+          }
+        }
+      },
+      State::AfterDoctypeName => match c {
+        Some(ch) if is_space(ch) => {}
+        Some('>') => self.emit_doctype(),
+        None => self.emit_doctype_at_eof(),
+        Some(_) => {
+          self.reconsume(c);
+          if self.consume_keyword("PUBLIC") {
+            self.state = State::AfterDoctypeKeyword(DoctypeId::Public);
+          } else if self.consume_keyword("SYSTEM") {
+            self.state = State::AfterDoctypeKeyword(DoctypeId::System);
+          } else {
+            let c = self.consume();
+            self.warn("`PUBLIC`, `SYSTEM` or `>`", c);
+            self.doctype.force_quirks = true;
+            self.state = State::BogusDoctype;
+          }
+        }
+      },
+      State::AfterDoctypeKeyword(id) => match c {
+        Some(ch) if is_space(ch) => self.state = State::BeforeDoctypeIdentifier(id),
+        Some(q) if q == '"' || q == '\'' => {
+          self.warn("whitespace before the identifier", c);
+          *self.doctype_id(id) = Some(String::new());
+          self.state = State::DoctypeIdentifier(id, q);
+        }
+        _ => self.missing_doctype_identifier(c),
+      },
+      State::BeforeDoctypeIdentifier(id) => match c {
+        Some(ch) if is_space(ch) => {}
+        Some(q) if q == '"' || q == '\'' => {
+          *self.doctype_id(id) = Some(String::new());
+          self.state = State::DoctypeIdentifier(id, q);
+        }
+        _ => self.missing_doctype_identifier(c),
+      },
+      State::DoctypeIdentifier(id, quote) => match c {
+        Some(ch) if ch == quote => {
+          self.state = match id {
+            DoctypeId::Public => State::AfterDoctypePublicIdentifier,
+            DoctypeId::System => State::AfterDoctypeSystemIdentifier,
+          };
+        }
+        Some('>') => {
+          self.warn(&format!("`{}`", quote), c);
+          self.doctype.force_quirks = true;
+          self.emit_doctype();
+        }
+        Some(ch) => {
+          if let Some(value) = self.doctype_id(id) {
+            value.push(ch);
+          }
+        }
+        None => self.emit_doctype_at_eof(),
+      },
+      State::AfterDoctypePublicIdentifier => match c {
+        Some(ch) if is_space(ch) => self.state = State::BetweenDoctypePublicAndSystemIdentifiers,
+        Some('>') => self.emit_doctype(),
+        Some(q) if q == '"' || q == '\'' => {
+          self.warn("whitespace before the system identifier", c);
+          self.doctype.system_id = Some(String::new());
+          self.state = State::DoctypeIdentifier(DoctypeId::System, q);
+        }
+        _ => self.missing_doctype_identifier(c),
+      },
+      State::BetweenDoctypePublicAndSystemIdentifiers => match c {
+        Some(ch) if is_space(ch) => {}
+        Some('>') => self.emit_doctype(),
+        Some(q) if q == '"' || q == '\'' => {
+          self.doctype.system_id = Some(String::new());
+          self.state = State::DoctypeIdentifier(DoctypeId::System, q);
+        }
+        _ => self.missing_doctype_identifier(c),
+      },
+      State::AfterDoctypeSystemIdentifier => match c {
+        Some(ch) if is_space(ch) => {}
+        Some('>') => self.emit_doctype(),
+        None => self.emit_doctype_at_eof(),
+        Some(_) => {
+          // unlike the other doctype errors, this doesn't force quirks mode
+          self.warn("`>`", c);
+          self.state = State::BogusDoctype;
+        }
+      },
+      State::BogusDoctype => match c {
+        Some('>') => self.emit_doctype(),
+        Some(_) => {}
+        None => {
+          self.emit_doctype();
+          self.emit_eof();
+        }
+      },
+    }
+  }
+
+  // consume a character reference after `&`, returning the
+  // text it stands for (or just `&`, leaving the rest to be
+  // tokenized as text, if it isn't one)
+  fn consume_character_reference(&mut self, in_attribute: bool) -> String {
+    let start = self.p.pos - 1;
+    match self.p.input[self.p.pos..].chars().next() {
+      Some(c) if c.is_ascii_alphanumeric() => self.consume_named_reference(start, in_attribute),
+      Some('#') => self.consume_numeric_reference(start),
+      _ => "&".to_string(),
+    }
+  }
+
+  // e.g. `&amp;`, or `&amp` for the legacy names that don't need a `;`
+  fn consume_named_reference(&mut self, start: usize, in_attribute: bool) -> String {
+    let rest = &self.p.input[self.p.pos..];
+    let name_len = rest.bytes().take(entities::LONGEST).take_while(|b| b.is_ascii_alphanumeric()).count();
+    let max_len = if rest[name_len..].starts_with(';') { name_len + 1 } else { name_len };
+
+    // the longest name that matches wins, e.g. `&notin;` over `&not`
+    let matched = (1..=max_len).rev().find_map(|len| entities::lookup(&rest[..len]).map(|value| (len, value)));
+    let (len, value) = match matched {
+      Some(matched) => matched,
+      None => {
+        if rest[name_len..].starts_with(';') {
+          let found = format!("`&{}`", &rest[..max_len]);
+          self.warn_at(start, "a known character reference", &found);
+        }
+        return "&".to_string();
+      }
+    };
+
+    let name = rest[..len].to_string();
+    self.p.pos += len;
+    if !name.ends_with(';') {
+      // for compatibility, `?a=1&copy=2` in an attribute keeps its `&copy`
+      let next = self.p.input[self.p.pos..].chars().next();
+      if in_attribute && next.is_some_and(|c| c == '=' || c.is_ascii_alphanumeric()) {
+        return format!("&{}", name);
+      }
+      self.warn_at(start, "`;` after the character reference", &format!("`&{}`", name));
+    }
+
+    value.to_string()
+  }
+
+  // e.g. `&#169;` or `&#xA9;`
+  fn consume_numeric_reference(&mut self, start: usize) -> String {
+    let after_ampersand = self.p.pos;
+    self.p.consume_char();
+    let hex = self.consume_keyword("x");
+    let digits = if hex {
+      self.p.consume_while(|c| c.is_ascii_hexdigit())
+    } else {
+      self.p.consume_while(|c| c.is_ascii_digit())
+    };
+
+    if digits.is_empty() {
+      // not a reference after all: `&#` is just text
+      self.warn_at(start, "digits in the character reference", &self.found_after(self.p.pos));
+      self.p.pos = after_ampersand;
+      return "&".to_string();
+    }
+
+    if self.p.starts_with(";") {
+      self.p.consume_char();
+    } else {
+      let found = format!("`{}`", &self.p.input[start..self.p.pos]);
+      self.warn_at(start, "`;` after the character reference", &found);
+    }
+
+    // anything too big to parse is out of range anyway
+    let code = u32::from_str_radix(&digits, if hex { 16 } else { 10 }).unwrap_or(u32::MAX);
+    let found = format!("`{}`", &self.p.input[start..self.p.pos]);
+    let c = match code {
+      0 | 0xD800..=0xDFFF | 0x110000.. => {
+        self.warn_at(start, "a valid character reference", &found);
+        '\u{FFFD}'
+      }
+      0x80..=0x9F => {
+        // C1 controls are read as windows-1252, like browsers do
+        self.warn_at(start, "a valid character reference", &found);
+        windows_1252(code)
+      }
+      _ => {
+        // other controls are kept, but still an error
+        let c = char::from_u32(code).unwrap();
+        if c == '\r' || (c.is_control() && !is_space(c)) {
+          self.warn_at(start, "a valid character reference", &found);
+        }
+        c
+      }
+    };
+
+    c.to_string()
+  }
+
+  // describe the char at `pos`, for a warning
+  fn found_after(&self, pos: usize) -> String {
+    match self.p.input[pos..].chars().next() {
+      Some(c) => format!("`{}`", c),
+      None => "end of input".to_string(),
+    }
+  }
+
+  // a doctype that's missing an identifier (or has something
+  // else where one should be) forces quirks mode
+  fn missing_doctype_identifier(&mut self, c: Option<char>) {
+    match c {
+      Some('>') => {
+        self.warn("a doctype identifier", c);
+        self.doctype.force_quirks = true;
+        self.emit_doctype();
+      }
+      None => self.emit_doctype_at_eof(),
+      Some(_) => {
+        self.warn("a quoted doctype identifier", c);
+        self.doctype.force_quirks = true;
+        self.state = State::BogusDoctype;
+      }
+    }
+  }
+}
+
+// the windows-1252 char for a C1 control code (0x80 to 0x9F)
+fn windows_1252(code: u32) -> char {
+  let mapped = match code {
+    0x80 => 0x20AC, 0x82 => 0x201A, 0x83 => 0x0192, 0x84 => 0x201E,
+    0x85 => 0x2026, 0x86 => 0x2020, 0x87 => 0x2021, 0x88 => 0x02C6,
+    0x89 => 0x2030, 0x8A => 0x0160, 0x8B => 0x2039, 0x8C => 0x0152,
+    0x8E => 0x017D, 0x91 => 0x2018, 0x92 => 0x2019, 0x93 => 0x201C,
+    0x94 => 0x201D, 0x95 => 0x2022, 0x96 => 0x2013, 0x97 => 0x2014,
+    0x98 => 0x02DC, 0x99 => 0x2122, 0x9A => 0x0161, 0x9B => 0x203A,
+    0x9C => 0x0153, 0x9E => 0x017E, 0x9F => 0x0178,
+    // 0x81, 0x8D, 0x8F, 0x90 and 0x9D are left alone
+    _ => code,
+  };
+  char::from_u32(mapped).unwrap()
+}
+
+// is `c` one of the chars HTML counts as whitespace?
+// (CR is gone once newlines are normalized)
+pub fn is_space(c: char) -> bool {
+  matches!(c, '\t' | '\n' | '\x0C' | ' ')
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Doctype, State, Tag, Token, Tokenizer};
+
+  // tokenize `source` up to EOF, joining runs of characters
+  // into a single string token for readability
+  fn tokenize(source: &str) -> Vec<Token> {
+    let mut tokenizer = Tokenizer::new(source.to_string());
+    collect(&mut tokenizer)
+  }
+
+  fn collect(tokenizer: &mut Tokenizer) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut text = String::new();
+    loop {
+      let token = tokenizer.next_token();
+      if let Token::Character(c) = token {
+        text.push(c);
+        continue;
+      }
+      if !text.is_empty() {
+        tokens.push(Token::Comment(format!("text:{}", text)));
+        text.clear();
+      }
+      if token == Token::Eof { break }
+      tokens.push(token);
+    }
+    tokens
+  }
+
+  fn text(s: &str) -> Token {
+    Token::Comment(format!("text:{}", s))
+  }
+
+  fn start(name: &str, attributes: &[(&str, &str)]) -> Token {
+    Token::StartTag(Tag {
+      name: name.to_string(),
+      self_closing: false,
+      attributes: attributes.iter().map(|&(n, v)| (n.to_string(), v.to_string())).collect(),
+    })
+  }
+
+  fn end(name: &str) -> Token {
+    Token::EndTag(Tag { name: name.to_string(), ..Tag::default() })
+  }
+
+  #[test]
+  fn tags_and_attributes() {
+    assert_eq!(tokenize("<DIV id=main class='a b' title=\"x\">hi</Div>"), vec![
+      start("div", &[("id", "main"), ("class", "a b"), ("title", "x")]),
+      text("hi"),
+      end("div"),
+    ]);
+
+    match tokenize("<br/>")[0] {
+      Token::StartTag(ref tag) => assert!(tag.self_closing),
+      _ => panic!("expected a start tag"),
+    }
+  }
+
+  #[test]
+  fn attribute_names_and_values() {
+    let mut tokenizer = Tokenizer::new("<input DISABLED data-foo=a:b xml:lang=en a_b.c='' id=1 ID=2 id=3>".to_string());
+    assert_eq!(collect(&mut tokenizer), vec![
+      start("input", &[("disabled", ""), ("data-foo", "a:b"), ("xml:lang", "en"), ("a_b.c", ""), ("id", "1")]),
+    ]);
+
+    let duplicates: Vec<(usize, &str)> = tokenizer.warnings.iter()
+      .map(|w| (w.column, w.found.as_str())).collect();
+    assert_eq!(duplicates, vec![(56, "`id`"), (61, "`id`")]);
+  }
+
+  #[test]
+  fn comments() {
+    assert_eq!(tokenize("<!-- a -- b --><!---->x<!-->"), vec![
+      Token::Comment(" a -- b ".to_string()),
+      Token::Comment(String::new()),
+      text("x"),
+      Token::Comment(String::new()),
+    ]);
+    assert_eq!(tokenize("<?xml version?>"), vec![Token::Comment("?xml version?".to_string())]);
+  }
+
+  #[test]
+  fn doctypes() {
+    assert_eq!(tokenize("<!DOCTYPE html>"), vec![Token::Doctype(Doctype {
+      name: Some("html".to_string()),
+      ..Doctype::default()
+    })]);
+    assert_eq!(tokenize("<!doctype HTML PUBLIC \"-//W3C//DTD HTML 4.01//EN\" 'x.dtd'>"),
+               vec![Token::Doctype(Doctype {
+                 name: Some("html".to_string()),
+                 public_id: Some("-//W3C//DTD HTML 4.01//EN".to_string()),
+                 system_id: Some("x.dtd".to_string()),
+                 force_quirks: false,
+               })]);
+    match tokenize("<!DOCTYPE>")[0] {
+      Token::Doctype(ref doctype) => assert!(doctype.force_quirks),
+      _ => panic!("expected a doctype"),
+    }
+  }
+
+  #[test]
+  fn raw_text_ends_at_the_appropriate_end_tag() {
+    let mut tokenizer = Tokenizer::new("<style>a</b> </styles></style>x".to_string());
+    assert_eq!(tokenizer.next_token(), start("style", &[]));
+    tokenizer.switch_to(State::Rawtext);
+    assert_eq!(collect(&mut tokenizer), vec![text("a</b> </styles>"), end("style"), text("x")]);
+  }
+
+  #[test]
+  fn raw_text_end_tags_start_at_their_less_than_sign() {
+    for &(source, state, name, start) in [("<script>x</script>", State::Rawtext, "script", 9),
+                                          ("<title>x</title>", State::Rcdata, "title", 8)].iter() {
+      let mut tokenizer = Tokenizer::new(source.to_string());
+      tokenizer.next_token();
+      tokenizer.switch_to(state);
+      assert_eq!(tokenizer.next_token(), Token::Character('x'));
+      assert_eq!(tokenizer.next_token(), end(name));
+      assert_eq!(tokenizer.token_start, start);
+    }
+
+    // so warnings about the end tag point at its `<`
+    let mut tokenizer = Tokenizer::new("<style>a</style x>".to_string());
+    tokenizer.next_token();
+    tokenizer.switch_to(State::Rawtext);
+    collect(&mut tokenizer);
+    assert_eq!(tokenizer.warnings[0].column, 9);
+  }
+
+  #[test]
+  fn character_references() {
+    assert_eq!(tokenize("&amp;&lt;&#169;&#x1F600;&#X41;&notin;&notit;&amp &ampx &bogus; & &#;"),
+               vec![text("&<\u{a9}\u{1f600}A\u{2209}\u{ac}it;& &x &bogus; & &#;")]);
+    assert_eq!(tokenize("&#0;&#x80;&#xD800;&#1114112;&#99999999999;"),
+               vec![text("\u{fffd}\u{20ac}\u{fffd}\u{fffd}\u{fffd}")]);
+
+    // legacy references before `=` or alphanumerics are kept in attributes
+    assert_eq!(tokenize("<a href=\"?x=1&copy=2&copy;&copy-\" title=&lt>"),
+               vec![start("a", &[("href", "?x=1&copy=2\u{a9}\u{a9}-"), ("title", "<")])]);
+
+    // but not in raw text
+    let mut tokenizer = Tokenizer::new("<style>&amp;</style>".to_string());
+    tokenizer.next_token();
+    tokenizer.switch_to(State::Rawtext);
+    assert_eq!(collect(&mut tokenizer), vec![text("&amp;"), end("style")]);
+  }
+
+  #[test]
+  fn character_reference_warnings() {
+    let mut tokenizer = Tokenizer::new("a&amp b\n&#65 &#; &nope;".to_string());
+    assert_eq!(collect(&mut tokenizer), vec![text("a& b\nA &#; &nope;")]);
+
+    let warnings: Vec<(usize, usize, &str)> = tokenizer.warnings.iter()
+      .map(|w| (w.line, w.column, w.found.as_str())).collect();
+    assert_eq!(warnings, vec![(1, 2, "`&amp`"), (2, 1, "`&#65`"), (2, 6, "`;`"), (2, 10, "`&nope;`")]);
+  }
+
+  #[test]
+  fn errors_are_recovered_from_with_warnings() {
+    let mut tokenizer = Tokenizer::new("a < b\n<p class=\"x\"id=y>".to_string());
+    assert_eq!(collect(&mut tokenizer), vec![text("a < b\n"), start("p", &[("class", "x"), ("id", "y")])]);
+
+    let warnings = &tokenizer.warnings;
+    assert_eq!(warnings.len(), 2);
+    assert_eq!((warnings[0].line, warnings[0].column), (1, 4));
+    assert_eq!(warnings[0].expected.as_str(), "a tag name");
+    assert_eq!((warnings[1].line, warnings[1].column), (2, 13));
+  }
+}
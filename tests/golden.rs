@@ -0,0 +1,76 @@
+//! golden-file tests
+//! each `tests/golden/<name>.html` (plus `<name>.css`, if there is one)
+//! is run through the pipeline and its DOM, style and layout dumps are
+//! compared with `<name>.dom`, `<name>.style` and `<name>.layout`.
+//! set BLESS=1 to write the current output as the expected output.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use toy_browser::{css, dom, html, layout, style};
+
+const GOLDEN_DIR: &str = "tests/golden";
+const VIEWPORT_WIDTH: f32 = 800.0;
+
+#[test]
+fn golden_files() {
+  let bless = env::var_os("BLESS").is_some();
+  let mut failures = Vec::new();
+
+  let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join(GOLDEN_DIR);
+  let mut paths: Vec<_> = fs::read_dir(&dir).unwrap()
+    .map(|entry| entry.unwrap().path())
+    .collect();
+  paths.sort();
+
+  for path in paths.iter().filter(|path| path.extension().is_some_and(|ext| ext == "html")) {
+    for (extension, actual) in render(path) {
+      let expected_path = path.with_extension(extension);
+
+      if bless {
+        fs::write(&expected_path, &actual).unwrap();
+        continue;
+      }
+
+      let expected = fs::read_to_string(&expected_path).unwrap_or_default();
+      if expected != actual {
+        failures.push(format!("{} does not match:\n--- expected\n{}--- actual\n{}",
+                              expected_path.display(), expected, actual));
+      }
+    }
+  }
+
+  if !failures.is_empty() {
+    panic!("{}\n(run with BLESS=1 to update the expected output)", failures.join("\n"));
+  }
+}
+
+// run a golden test case, returning (extension, dump) for each stage
+fn render(html_path: &Path) -> Vec<(&'static str, String)> {
+  let source = fs::read_to_string(html_path).unwrap();
+  let dom = html::parse(source);
+
+  // document stylesheets first, then the test's own css file
+  let mut stylesheets = Vec::new();
+  if let dom::NodeType::Document(ref document) = dom.node_type {
+    for sheet in document.stylesheets().iter().filter(|sheet| sheet.applies_to_screen()) {
+      if let Some(sheet) = sheet.inline() {
+        stylesheets.push((style::Origin::Author, sheet));
+      }
+    }
+  }
+  let css_path = html_path.with_extension("css");
+  let test_sheet = css_path.exists().then(|| css::parse(fs::read_to_string(&css_path).unwrap()));
+  if let Some(ref sheet) = test_sheet {
+    stylesheets.push((style::Origin::Author, sheet));
+  }
+
+  let style_root = style::style_tree(&dom, &stylesheets);
+
+  let mut viewport: layout::Dimensions = Default::default();
+  viewport.content.width = VIEWPORT_WIDTH;
+  let layout_root = layout::layout_tree(&style_root, viewport);
+
+  vec![("dom", dom.dump()), ("style", style_root.dump()), ("layout", layout_root.dump())]
+}